@@ -0,0 +1,240 @@
+use futures::future::{self, Either};
+use futures::Future;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::{ApiQuery, Connection, DegeocodeQuery, Error, GeocodeQuery, Reply, Result};
+
+/// A cached reply, along with when it was inserted and last used
+#[derive(Debug)]
+struct Entry {
+    inserted: Instant,
+    last_used: u64,
+    replies: Vec<Reply>,
+}
+
+/// A bounded map that expires entries after a time to live,
+/// and evicts the least recently used entry when full
+#[derive(Debug)]
+struct Lru {
+    entries: HashMap<String, Entry>,
+    max_entries: usize,
+    tick: u64,
+    ttl: Duration,
+}
+
+impl Lru {
+    fn new(max_entries: usize, ttl: Duration) -> Self {
+        Lru {
+            entries: HashMap::new(),
+            max_entries,
+            tick: 0,
+            ttl,
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<Vec<Reply>> {
+        self.get_at(key, Instant::now())
+    }
+
+    fn get_at(&mut self, key: &str, now: Instant) -> Option<Vec<Reply>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let ttl = self.ttl;
+        let expired = match self.entries.get_mut(key) {
+            Some(ref entry) if now.duration_since(entry.inserted) >= ttl => true,
+            Some(entry) => {
+                entry.last_used = tick;
+                return Some(entry.replies.clone());
+            },
+            None => return None,
+        };
+        if expired {
+            self.entries.remove(key);
+        }
+        None
+    }
+
+    fn insert(&mut self, key: String, replies: Vec<Reply>) {
+        self.insert_at(key, replies, Instant::now())
+    }
+
+    fn insert_at(&mut self, key: String, replies: Vec<Reply>, now: Instant) {
+        if self.max_entries == 0 {
+            return;
+        }
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            let ttl = self.ttl;
+            self.entries.retain(|_, entry| now.duration_since(entry.inserted) < ttl);
+        }
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.max_entries {
+            let oldest = self.entries.iter()
+                .min_by_key(|&(_, entry)| entry.last_used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, Entry {
+            inserted: now,
+            last_used: self.tick,
+            replies,
+        });
+    }
+}
+
+/// A connection that remembers the replies to recent queries
+///
+/// Replies are cached in memory keyed on the query string that would be sent to the API,
+/// so every parameter (address, components, bounds, language, region, filters) participates in the key.
+/// For example, the same address queried in French and in English is cached separately.
+/// The address is compared case-insensitively, while every other parameter, such as a session token, must match exactly.
+/// Only successful replies are cached, and clones of a connection share its cache.
+///
+/// Cached replies expire once they are older than the time to live, as both the API's data and quota accounting change over time.
+/// Once `max_entries` replies are cached, expired ones are dropped, and if none have expired the least recently used one is evicted.
+#[derive(Clone)]
+pub struct CachedConnection {
+    inner: Connection,
+    cache: Rc<RefCell<Lru>>,
+}
+
+impl CachedConnection {
+    /// Wraps the specified connection with a cache of at most `max_entries` replies, each kept for at most `ttl`
+    pub fn new(inner: Connection, max_entries: usize, ttl: Duration) -> Self {
+        CachedConnection {
+            inner,
+            cache: Rc::new(RefCell::new(Lru::new(max_entries, ttl))),
+        }
+    }
+
+    /// The number of cached replies, including any that have expired but not yet been dropped
+    pub fn len(&self) -> usize {
+        self.cache.borrow().entries.len()
+    }
+
+    /// Whether no replies are cached
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get the address of the specified coordinates, from the cache if possible
+    ///
+    /// As with `Connection::degeocode`, a `ZeroResults` reply gives no replies rather than an error,
+    /// and the query's filters are applied client-side.
+    pub fn degeocode(&self, coordinates: impl Into<DegeocodeQuery>) -> impl Future<Item = Vec<Reply>, Error = Error> {
+        let query = coordinates.into();
+        let filters = query.clone();
+        self.get(query)
+            .or_else(Connection::zero_results_as_empty)
+            .map(move |replies| filters.retain_matching(replies))
+    }
+
+    /// Get the coordinates of the specified address, from the cache if possible
+    ///
+    /// As with `Connection::geocode`, the query is retried in each of its retry languages
+    /// while there are no results, and each language is cached separately.
+    pub fn geocode(&self, address: impl Into<GeocodeQuery>) -> impl Future<Item = Vec<Reply>, Error = Error> {
+        let connection = self.clone();
+        Connection::retry_languages(address.into(), move |query| connection.get(query))
+    }
+
+    /// The key under which replies to the specified query are cached
+    fn key(query: &impl ApiQuery) -> Result<String> {
+        let query = ::serde_urlencoded::to_string(query)?;
+        let pairs = ::url::form_urlencoded::parse(query.as_bytes()).map(|(name, value)| {
+            let value = if name == "address" { value.to_lowercase() } else { value.into_owned() };
+            (name, value)
+        });
+        Ok(::url::form_urlencoded::Serializer::new(String::new()).extend_pairs(pairs).finish())
+    }
+
+    /// Perform the specified query, unless it is cached when the future is first polled
+    fn get(&self, query: impl ApiQuery) -> impl Future<Item = Vec<Reply>, Error = Error> {
+        let cache = self.cache.clone();
+        let inner = self.inner.clone();
+        future::lazy(move || {
+            let key = match Self::key(&query) {
+                Ok(key) => key,
+                Err(e) => return Either::A(future::err(e)),
+            };
+            if let Some(replies) = cache.borrow_mut().get(&key) {
+                return Either::A(future::ok(replies));
+            }
+            Either::B(inner.get(query).map(move |replies| {
+                cache.borrow_mut().insert(key, replies.clone());
+                replies
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json;
+
+    fn replies() -> Vec<Reply> {
+        let fixture: serde_json::Value = serde_json::from_str(include_str!("../tests/fixtures/geocode.json")).unwrap();
+        serde_json::from_value(fixture["results"].clone()).unwrap()
+    }
+
+    #[test]
+    fn lru_evicts_least_recently_used() {
+        let mut lru = Lru::new(2, Duration::from_secs(60));
+        lru.insert("a".to_string(), replies());
+        lru.insert("b".to_string(), replies());
+        assert!(lru.get("a").is_some());
+        lru.insert("c".to_string(), replies());
+        assert!(lru.get("a").is_some());
+        assert!(lru.get("b").is_none());
+        assert!(lru.get("c").is_some());
+        assert_eq!(lru.entries.len(), 2);
+    }
+
+    #[test]
+    fn lru_disabled() {
+        let mut lru = Lru::new(0, Duration::from_secs(60));
+        lru.insert("a".to_string(), replies());
+        assert!(lru.get("a").is_none());
+    }
+
+    #[test]
+    fn lru_expires() {
+        let start = Instant::now();
+        let mut lru = Lru::new(2, Duration::from_secs(60));
+        lru.insert_at("a".to_string(), replies(), start);
+        lru.insert_at("b".to_string(), replies(), start + Duration::from_secs(30));
+        assert!(lru.get_at("a", start + Duration::from_secs(59)).is_some());
+        assert!(lru.get_at("a", start + Duration::from_secs(60)).is_none());
+        assert_eq!(lru.entries.len(), 1);
+
+        // Expired entries are dropped before evicting the least recently used one
+        lru.insert_at("c".to_string(), replies(), start + Duration::from_secs(40));
+        lru.get_at("b", start + Duration::from_secs(41));
+        lru.insert_at("d".to_string(), replies(), start + Duration::from_secs(95));
+        assert!(lru.get_at("b", start + Duration::from_secs(95)).is_none());
+        assert!(lru.get_at("c", start + Duration::from_secs(95)).is_some());
+        assert!(lru.get_at("d", start + Duration::from_secs(95)).is_some());
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn key_includes_parameters() {
+        use super::super::Language;
+        let english = CachedConnection::key(&GeocodeQuery::new("Paris").language(Language::English)).unwrap();
+        let french = CachedConnection::key(&GeocodeQuery::new("Paris").language(Language::French)).unwrap();
+        assert_ne!(english, french);
+    }
+
+    #[test]
+    fn key_only_ignores_case_of_address() {
+        assert_eq!(CachedConnection::key(&GeocodeQuery::new("PARIS")).unwrap(), CachedConnection::key(&GeocodeQuery::new("paris")).unwrap());
+        let lower = CachedConnection::key(&GeocodeQuery::new("Paris").session_token("token")).unwrap();
+        let upper = CachedConnection::key(&GeocodeQuery::new("Paris").session_token("TOKEN")).unwrap();
+        assert_ne!(lower, upper);
+    }
+}
@@ -0,0 +1,70 @@
+//! Language and region codes as validated strings, available with or without the full enums
+
+use super::Result;
+#[cfg(feature="full-enums")]
+use super::{serde_util, Language, Region};
+
+/// A language code, eg `en` or `zh-CN`
+///
+/// From https://developers.google.com/maps/faq#languagesupport
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+pub struct LanguageCode(String);
+
+impl LanguageCode {
+    /// Creates a language code, failing unless it is of the form `ll` or `ll-RR`
+    pub fn new(code: impl Into<String>) -> Result<Self> {
+        let code = code.into();
+        let mut parts = code.splitn(2, '-');
+        let language = parts.next().unwrap_or("");
+        let valid = (2..=3).contains(&language.len())
+            && language.bytes().all(|b| b.is_ascii_lowercase())
+            && parts.next().map_or(true, |subtag| (2..=4).contains(&subtag.len()) && subtag.bytes().all(|b| b.is_ascii_alphanumeric()));
+        if !valid {
+            bail!("Invalid language code {:?}", code);
+        }
+        Ok(LanguageCode(code))
+    }
+
+    /// The language code as sent to the API
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature="full-enums")]
+impl From<Language> for LanguageCode {
+    fn from(language: Language) -> Self {
+        LanguageCode(serde_util::to_plain_string(&language).expect("Languages serialize as their code"))
+    }
+}
+
+/// A country code top-level domain, eg `fr`
+///
+/// From https://icannwiki.org/Country_code_top-level_domain
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize)]
+pub struct RegionCode(String);
+
+impl RegionCode {
+    /// Creates a region code, failing unless it is two ASCII letters
+    ///
+    /// The code is lowercased, and a leading dot is accepted, eg `.FR` becomes `fr`.
+    pub fn new(code: impl Into<String>) -> Result<Self> {
+        let code = code.into().trim_start_matches('.').to_ascii_lowercase();
+        if code.len() != 2 || !code.bytes().all(|b| b.is_ascii_lowercase()) {
+            bail!("Invalid region code {:?}", code);
+        }
+        Ok(RegionCode(code))
+    }
+
+    /// The region code as sent to the API
+    pub fn code(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature="full-enums")]
+impl From<Region> for RegionCode {
+    fn from(region: Region) -> Self {
+        RegionCode(serde_util::to_plain_string(&region).expect("Regions serialize as their code"))
+    }
+}
@@ -0,0 +1,38 @@
+//! Serde helpers for coordinates in the `{"lat": ..., "lng": ...}` form used by replies
+//!
+//! The `Serialize` implementation of `Coordinates` produces the `lat,lng` string expected in query parameters.
+//! To serialize coordinates as they appear in replies instead, annotate the field:
+//!
+//! ```
+//! extern crate google_geocoding;
+//! #[macro_use]
+//! extern crate serde_derive;
+//!
+//! use google_geocoding::Coordinates;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Marker {
+//!     #[serde(with = "google_geocoding::lat_lng")]
+//!     position: Coordinates,
+//! }
+//! # fn main() {}
+//! ```
+
+use serde::{Deserialize, Deserializer, Serializer};
+use serde::ser::SerializeStruct;
+use std;
+
+use super::Coordinates;
+
+/// Serialize coordinates as a `{"lat": ..., "lng": ...}` object
+pub fn serialize<S>(coordinates: &Coordinates, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
+    let mut s = serializer.serialize_struct("Coordinates", 2)?;
+    s.serialize_field("lat", &coordinates.latitude_degrees())?;
+    s.serialize_field("lng", &coordinates.longitude_degrees())?;
+    s.end()
+}
+
+/// Deserialize coordinates from a `{"lat": ..., "lng": ...}` object
+pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<Coordinates, D::Error> where D: Deserializer<'de> {
+    Coordinates::deserialize(deserializer)
+}
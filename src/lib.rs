@@ -30,11 +30,16 @@
 //! However, the full reply includes a great deal more information. For access to
 //! the full reply, see the lowlevel asynchronous API.
 //!
+//! The iterators returned report the number of results up front with `len()`.
+//! Their concrete type is left unspecified so that results could be fetched lazily
+//! should the API ever paginate them.
+//!
 //! ## Synchronous API (Advanced)
 //!
 //! The GeocodeQuery and DegeocodeQuery objects can be used for more complex lookups
 //!
 //! ```
+//! # #[cfg(feature = "full-enums")] {
 //! use google_geocoding::{GeocodeQuery, Language, Region, geocode};
 //! let query = GeocodeQuery::new("1600 Amphitheater Parkway, Mountain View, CA")
 //!     .language(Language::English)
@@ -42,6 +47,7 @@
 //! for coordinates in geocode(query).unwrap() {
 //!     println!("{}", coordinates);
 //! }
+//! # }
 //! ```
 //!
 //! ## Asynchronous API
@@ -86,10 +92,12 @@ extern crate itertools;
 #[macro_use]
 extern crate log;
 extern crate nav_types;
+extern crate rand;
 extern crate reqwest;
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
+#[macro_use]
 extern crate serde_json;
 extern crate serde_urlencoded;
 #[macro_use]
@@ -98,26 +106,45 @@ extern crate strum;
 #[macro_use]
 extern crate strum_macros;
 extern crate tokio_core;
+extern crate unicode_normalization;
 extern crate url;
+mod cache;
+mod codes;
+pub mod lat_lng;
 mod serde_util;
 
+pub use cache::CachedConnection;
+pub use codes::{LanguageCode, RegionCode};
+
 use futures::{Future, Stream};
+use futures::future::{self, Loop};
+use futures::stream;
 
-use failure::Error;
-pub use nav_types::WGS84;
-use reqwest::unstable::async::Client;
+use failure::{Error, Fail};
+pub use nav_types::{ECEF, WGS84};
+use nav_types::NVector;
+use reqwest::header::{Headers, RetryAfter};
+use reqwest::unstable::async::{Chunk, Client};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::RefCell;
 use std::cmp::Eq;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt::{self, Debug, Display, Formatter};
 use std::hash::Hash;
-use tokio_core::reactor::Core;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
+use tokio_core::reactor::{Core, Timeout};
 use url::Url;
 
 type Result<T> = std::result::Result<T, Error>;
 
+/// The endpoint of the Google Static Maps API, see `Reply::static_map_url`
+const STATIC_MAP_ROOT: &str = "https://maps.googleapis.com/maps/api/staticmap";
+
 /// One component of a separated address
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct AddressComponent {
     /// The full text description or name of the address component as returned by the Geocoder.
     long_name: String,
@@ -128,23 +155,124 @@ pub struct AddressComponent {
     types: Vec<Type>,
 }
 
+impl AddressComponent {
+    /// The full text description or name of the address component as returned by the Geocoder.
+    pub fn long_name(&self) -> &str {
+        &self.long_name
+    }
+
+    /// An abbreviated textual name for the address component, if available.
+    pub fn short_name(&self) -> &str {
+        &self.short_name
+    }
+
+    /// The type of the address component.
+    pub fn types(&self) -> &[Type] {
+        &self.types
+    }
+
+    /// Whether this component is of the specified type
+    pub fn is(&self, i_type: Type) -> bool {
+        self.types.contains(&i_type)
+    }
+}
+
+/// Both forms of the name of an address component
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ComponentName {
+    /// The full text description or name, eg "California"
+    pub long_name: String,
+    /// The abbreviated name, eg "CA"
+    pub short_name: String,
+}
+
+impl<'a> From<&'a AddressComponent> for ComponentName {
+    fn from(v: &'a AddressComponent) -> Self {
+        ComponentName {
+            long_name: v.long_name.clone(),
+            short_name: v.short_name.clone(),
+        }
+    }
+}
+
+/// An address broken down into its commonly used components
+///
+/// Each field carries both the long and short name, as conventions differ by field:
+/// countries and states are commonly shown by their short codes, while localities use the long name.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct StructuredAddress {
+    /// The precise street number
+    pub street_number: Option<ComponentName>,
+    /// The named route, eg the street
+    pub route: Option<ComponentName>,
+    /// The named neighborhood
+    pub neighborhood: Option<ComponentName>,
+    /// The first-order civil entity below a locality
+    pub sublocality: Option<ComponentName>,
+    /// The incorporated city or town
+    pub locality: Option<ComponentName>,
+    /// The second-order civil entity below the country level (counties in the United States)
+    pub administrative_area_level_2: Option<ComponentName>,
+    /// The first-order civil entity below the country level (states in the United States)
+    pub administrative_area_level_1: Option<ComponentName>,
+    /// The postal code
+    pub postal_code: Option<ComponentName>,
+    /// The country
+    pub country: Option<ComponentName>,
+}
+
 /// Position information
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Geometry {
     /// The geocoded latitude, longitude value.
     /// For normal address lookups, this field is typically the most important.
+    #[serde(serialize_with="lat_lng::serialize")]
     pub location: Coordinates,
     /// Stores additional data about the specified location
     pub location_type: LocationType,
     /// the recommended viewport for displaying the returned result, specified as two latitude,longitude values defining the southwest and northeast corner of the viewport bounding box. Generally the viewport is used to frame a result when displaying it to a user.
+    ///
+    /// Some sparse results omit the viewport entirely. In that case this is a zero-sized viewport at `location`.
     pub viewport: Viewport,
     /// The bounding box which can fully contain the returned result.
     /// Note that these bounds may not match the recommended viewport. (For example, San Francisco includes the Farallon islands, which are technically part of the city, but probably should not be returned in the viewport.)
+    #[serde(skip_serializing_if="Option::is_none")]
     pub bounds: Option<Viewport>
 }
 
+impl Geometry {
+    /// The bounds if present, otherwise the viewport
+    ///
+    /// This is usually what to fit a map to.
+    pub fn effective_bounds(&self) -> &Viewport {
+        self.bounds.as_ref().unwrap_or(&self.viewport)
+    }
+}
+
+impl<'de> Deserialize<'de> for Geometry {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error> where D: Deserializer<'de> {
+        #[derive(Deserialize)]
+        struct Helper {
+            location: Coordinates,
+            location_type: LocationType,
+            viewport: Option<Viewport>,
+            bounds: Option<Viewport>,
+        }
+        let x = Helper::deserialize(deserializer)?;
+        Ok(Geometry {
+            location: x.location,
+            location_type: x.location_type,
+            viewport: x.viewport.unwrap_or(Viewport {
+                northeast: x.location,
+                southwest: x.location,
+            }),
+            bounds: x.bounds,
+        })
+    }
+}
+
 /// What location Geometry refers to
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, EnumIter, Eq, Hash, PartialEq, Serialize)]
 #[serde(rename_all="SCREAMING_SNAKE_CASE")]
 pub enum LocationType {
     /// Indicates that the returned result is a precise geocode
@@ -163,6 +291,44 @@ pub enum LocationType {
 
     /// Indicates that the returned result is approximate.
     Approximate,
+
+    /// A location type this crate does not know about yet.
+    ///
+    /// This lets replies still decode if the API introduces a new location type.
+    /// It should not be used as a filter, as it serializes as `UNKNOWN` rather than the original value.
+    #[serde(other)]
+    Unknown,
+}
+
+impl LocationType {
+    /// How precise this location type is, from 0 (approximate or unknown) to 3 (rooftop)
+    pub fn precision(self) -> u8 {
+        match self {
+            LocationType::Rooftop => 3,
+            LocationType::RangeInterpolated => 2,
+            LocationType::GeometricCenter => 1,
+            LocationType::Approximate | LocationType::Unknown => 0,
+        }
+    }
+}
+
+/// How fine-grained a result is, ordered from the coarsest to the finest, see `Reply::granularity`
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Granularity {
+    /// The result has no recognized type and an approximate location
+    Unknown,
+    /// A country
+    Country,
+    /// A civil entity below the country level, eg a state or county
+    AdministrativeArea,
+    /// A city or town, or an area within or around one such as a sublocality, neighborhood or postal code
+    Locality,
+    /// A named route or an intersection
+    Route,
+    /// A precise street address
+    StreetAddress,
+    /// A named location such as a building, or a part of one
+    Premise,
 }
 
 /// An API set that deseriaizes as a JSON array and serializes with pipe spaces
@@ -176,16 +342,29 @@ impl<'de,T> Deserialize<'de> for ApiSet<T>
     }
 }
 
+impl<T> std::iter::FromIterator<T> for ApiSet<T>
+    where T: Eq + Hash + Serialize {
+    fn from_iter<I>(iter: I) -> Self where I: IntoIterator<Item = T> {
+        ApiSet(iter.into_iter().collect())
+    }
+}
+
 impl<T> Serialize for ApiSet<T>
     where T: Eq + Hash + Serialize {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
         use itertools::Itertools;
-        serializer.serialize_str(&self.0.iter().map(serde_util::variant_name).join("|"))
+        let mut values = self.0.iter()
+            .map(serde_util::to_plain_string)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(serde::ser::Error::custom)?;
+        // Sort so that equal sets always produce the same query
+        values.sort();
+        serializer.serialize_str(&values.iter().join("|"))
     }
 }
 
 /// A human-readable address of this location.
-#[derive(Debug,Deserialize)]
+#[derive(Clone,Debug,Deserialize,Serialize)]
 pub struct FormattedAddress(String);
 
 impl Display for FormattedAddress {
@@ -195,7 +374,7 @@ impl Display for FormattedAddress {
 }
 
 /// A reply from the Google geocoding API
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Reply {
     /// The separate components applicable to this address. 
     pub address_components: Vec<AddressComponent>,
@@ -209,25 +388,384 @@ pub struct Reply {
     pub formatted_address: FormattedAddress,
     /// Position information
     pub geometry: Geometry,
+    /// Indicates that the geocoder did not return an exact match for the original request, though it was able to match part of the requested address.
+    /// You may wish to examine the original request for misspellings and/or an incomplete address.
+    #[serde(default, skip_serializing_if="std::ops::Not::not")]
+    pub partial_match: bool,
     /// A unique identifier that can be used with other Google APIs.
     pub place_id: PlaceId,
     /// All the localities contained in a postal code.
     /// This is only present when the result is a postal code that contains multiple localities.
+    #[serde(skip_serializing_if="Option::is_none")]
     pub postcode_localities: Option<Vec<String>>,
 
     /// The type of the returned result. This array contains a set of zero or more tags identifying the type of feature returned in the result. For example, a geocode of "Chicago" returns "locality" which indicates that "Chicago" is a city, and also returns "political" which indicates it is a political entity.
     pub types: Vec<Type>,
 }
 
+impl Reply {
+    /// The first address component of the specified type, if any
+    pub fn component(&self, i_type: Type) -> Option<&AddressComponent> {
+        self.address_components.iter().find(|c| c.is(i_type))
+    }
+
+    /// Each administrative area present, with its level, from the coarsest (1, eg states) to the finest (5)
+    pub fn administrative_areas(&self) -> Vec<(u8, &AddressComponent)> {
+        [
+            Type::AdministrativeAreaLevel1,
+            Type::AdministrativeAreaLevel2,
+            Type::AdministrativeAreaLevel3,
+            Type::AdministrativeAreaLevel4,
+            Type::AdministrativeAreaLevel5,
+        ].iter()
+            .zip(1..)
+            .filter_map(|(&t, level)| self.component(t).map(|c| (level, c)))
+            .collect()
+    }
+
+    /// How fine-grained this result is, eg to keep only results of at least street-level precision
+    ///
+    /// This is the finest granularity among the result types.
+    /// Results without a recognized type, such as points of interest, are classified by their location type instead.
+    pub fn granularity(&self) -> Granularity {
+        let from_type = |t: &Type| match t {
+            Type::Premise | Type::Subpremise | Type::Floor | Type::Room => Some(Granularity::Premise),
+            Type::StreetAddress | Type::StreetNumber => Some(Granularity::StreetAddress),
+            Type::Route | Type::Intersection => Some(Granularity::Route),
+            Type::Locality | Type::Ward | Type::ColloquialArea | Type::Neighborhood | Type::PostalCode | Type::PostalTown
+                | Type::Sublocality | Type::SublocalityLevel1 | Type::SublocalityLevel2 | Type::SublocalityLevel3
+                | Type::SublocalityLevel4 | Type::SublocalityLevel5 => Some(Granularity::Locality),
+            Type::AdministrativeAreaLevel1 | Type::AdministrativeAreaLevel2 | Type::AdministrativeAreaLevel3
+                | Type::AdministrativeAreaLevel4 | Type::AdministrativeAreaLevel5 => Some(Granularity::AdministrativeArea),
+            Type::Country => Some(Granularity::Country),
+            _ => None,
+        };
+        self.types.iter().filter_map(from_type).max().unwrap_or(match self.geometry.location_type {
+            LocationType::Rooftop => Granularity::Premise,
+            LocationType::RangeInterpolated => Granularity::StreetAddress,
+            LocationType::GeometricCenter => Granularity::Route,
+            LocationType::Approximate | LocationType::Unknown => Granularity::Unknown,
+        })
+    }
+
+    /// A heuristic score from 0 to 1 of how confidently this result matches the query
+    ///
+    /// The score starts from the location type: 1.0 for `Rooftop`, 0.8 for `RangeInterpolated`,
+    /// 0.6 for `GeometricCenter` and 0.4 for `Approximate` or `Unknown`.
+    /// It is halved for a partial match, and multiplied by 0.75 if the result is coarser than a street address
+    /// (see `granularity`), so a rooftop street address that fully matched scores 1.0
+    /// and an approximate, partially matched locality 0.15.
+    pub fn confidence(&self) -> f32 {
+        let mut score = 0.4 + 0.2 * f32::from(self.geometry.location_type.precision());
+        if self.partial_match {
+            score *= 0.5;
+        }
+        if self.granularity() < Granularity::StreetAddress {
+            score *= 0.75;
+        }
+        score
+    }
+
+    /// The named neighborhood, if any
+    pub fn neighborhood(&self) -> Option<&AddressComponent> {
+        self.component(Type::Neighborhood)
+    }
+
+    /// The finest-grained sublocality, if any
+    ///
+    /// Sublocalities can be nested up to five levels deep, so this prefers the highest numbered level present,
+    /// falling back to a plain sublocality.
+    pub fn sublocality(&self) -> Option<&AddressComponent> {
+        [
+            Type::SublocalityLevel5,
+            Type::SublocalityLevel4,
+            Type::SublocalityLevel3,
+            Type::SublocalityLevel2,
+            Type::SublocalityLevel1,
+            Type::Sublocality,
+        ].iter().filter_map(|&t| self.component(t)).next()
+    }
+
+    /// Whether this result is a major intersection, usually of two major roads
+    pub fn is_intersection(&self) -> bool {
+        self.types.contains(&Type::Intersection)
+    }
+
+    /// The long names of the two routes that meet at this intersection
+    ///
+    /// The routes are taken from separate route components if present,
+    /// otherwise by splitting the intersection component on `&`.
+    /// Returns None if this result is not an intersection.
+    pub fn intersection_routes(&self) -> Option<(String, String)> {
+        if !self.is_intersection() {
+            return None;
+        }
+        let mut routes = self.address_components.iter().filter(|c| c.is(Type::Route));
+        if let (Some(first), Some(second)) = (routes.next(), routes.next()) {
+            return Some((first.long_name.clone(), second.long_name.clone()));
+        }
+        let intersection = self.component(Type::Intersection)?;
+        let mut parts = intersection.long_name.splitn(2, '&').map(str::trim);
+        match (parts.next(), parts.next()) {
+            (Some(first), Some(second)) if !first.is_empty() && !second.is_empty() => Some((first.to_string(), second.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Collects the commonly used address components into a structured address
+    pub fn to_structured_address(&self) -> StructuredAddress {
+        let name = |i_type| self.component(i_type).map(ComponentName::from);
+        StructuredAddress {
+            street_number: name(Type::StreetNumber),
+            route: name(Type::Route),
+            neighborhood: name(Type::Neighborhood),
+            sublocality: name(Type::Sublocality),
+            locality: name(Type::Locality),
+            administrative_area_level_2: name(Type::AdministrativeAreaLevel2),
+            administrative_area_level_1: name(Type::AdministrativeAreaLevel1),
+            postal_code: name(Type::PostalCode),
+            country: name(Type::Country),
+        }
+    }
+
+    /// Whether the location of this result lies within `radius` metres of `center`
+    ///
+    /// Distance is measured along the great circle, treating the earth as a sphere.
+    pub fn within_meters(&self, center: &Coordinates, radius: f64) -> bool {
+        self.geometry.location.distance_2d(center) <= radius
+    }
+
+    /// A concise one-line summary of the address, location, precision and place ID
+    ///
+    /// For example `1600 Amphitheatre Pkwy, Mountain View, CA 94043, USA at 37.42241,-122.08561 (Rooftop, place ID ChIJ2eUgeAK6j4ARbn5u_wAGqWA)`.
+    pub fn summary(&self) -> String {
+        format!("{} at {} ({:?}, place ID {})",
+            self.formatted_address,
+            self.geometry.location.to_precision(Coordinates::QUERY_PRECISION),
+            self.geometry.location_type,
+            self.place_id.0)
+    }
+
+    /// A Google Static Maps URL centered on this result with a marker at its location
+    ///
+    /// `size` is the width and height of the image in pixels.
+    /// No key is added, append one with `key=` if the Static Maps API requires it for the project.
+    pub fn static_map_url(&self, zoom: u8, size: (u32, u32)) -> String {
+        let location = self.geometry.location.to_precision(Coordinates::QUERY_PRECISION);
+        let mut url = Url::parse(STATIC_MAP_ROOT).expect("STATIC_MAP_ROOT is a valid URL");
+        url.query_pairs_mut()
+            .append_pair("center", &location)
+            .append_pair("zoom", &zoom.to_string())
+            .append_pair("size", &format!("{}x{}", size.0, size.1))
+            .append_pair("markers", &location);
+        url.into_string()
+    }
+
+    /// Splits this reply into its location and human-readable address
+    pub fn into_point_and_label(self) -> (Coordinates, FormattedAddress) {
+        (self.geometry.location, self.formatted_address)
+    }
+}
+
+/// Displays the summary of this reply, see `Reply::summary`
+impl Display for Reply {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.summary())
+    }
+}
+
+impl From<Reply> for (Coordinates, FormattedAddress) {
+    fn from(v: Reply) -> Self {
+        v.into_point_and_label()
+    }
+}
+
+/// Helpers for the list of candidates returned by a query
+///
+/// The API returns candidates ordered by relevance, with the most relevant first.
+pub trait ReplyList {
+    /// The most relevant candidate
+    fn best_match(&self) -> Option<&Reply>;
+
+    /// The candidate with the most precise location type
+    ///
+    /// Candidates sharing the same precision are tie-broken by relevance.
+    fn most_precise(&self) -> Option<&Reply>;
+
+    /// The most relevant candidate that is not a partial match and whose location type is neither `Approximate` nor `Unknown`
+    fn best_confident(&self) -> Option<&Reply>;
+
+    /// The average location of the candidates
+    ///
+    /// Locations are averaged in ECEF space and projected back onto the ellipsoid,
+    /// which unlike averaging latitudes and longitudes is correct near the poles and antimeridian.
+    /// Returns None if there are no candidates, or if their locations cancel out, eg two antipodes.
+    fn centroid(&self) -> Option<Coordinates>;
+}
+
+impl ReplyList for [Reply] {
+    fn best_match(&self) -> Option<&Reply> {
+        self.first()
+    }
+
+    fn most_precise(&self) -> Option<&Reply> {
+        // max_by_key returns the last maximum, so search in reverse to prefer the most relevant
+        self.iter().rev().max_by_key(|r| r.geometry.location_type.precision())
+    }
+
+    fn best_confident(&self) -> Option<&Reply> {
+        self.iter().find(|r| !r.partial_match && r.geometry.location_type.precision() > 0)
+    }
+
+    fn centroid(&self) -> Option<Coordinates> {
+        if self.is_empty() {
+            return None;
+        }
+        let n = self.len() as f64;
+        let (x, y, z) = self.iter()
+            .map(|r| r.geometry.location.to_ecef())
+            .fold((0f64, 0f64, 0f64), |(x, y, z), p| (x + p.x(), y + p.y(), z + p.z()));
+        let (x, y, z) = (x / n, y / n, z / n);
+        // A centroid within a metre of the centre of the earth means the locations cancel out
+        if (x * x + y * y + z * z).sqrt() < 1.0 {
+            return None;
+        }
+        let projected = WGS84::from(ECEF::new(x, y, z));
+        // The longitude is computed directly, as the conversion can stray just past the antimeridian
+        Coordinates::validated(projected.latitude_degrees(), y.atan2(x).to_degrees()).ok()
+    }
+}
+
+/// Client-side filters for iterators over replies
+///
+/// Unlike the server-side `result_type` filter, these need no API key and work on already fetched results.
+/// They apply to iterators over both `Reply` and `&Reply`.
+pub trait ReplyIterator : Iterator + Sized where Self::Item: std::borrow::Borrow<Reply> {
+    /// Keep only the replies whose top-level types include the specified type
+    fn filter_type(self, i_type: Type) -> FilterType<Self> {
+        FilterType {
+            inner: self,
+            result_type: i_type,
+        }
+    }
+}
+
+impl<I> ReplyIterator for I where I: Iterator, I::Item: std::borrow::Borrow<Reply> {}
+
+/// An iterator over the replies of a specific type, see `ReplyIterator::filter_type`
+#[derive(Clone, Debug)]
+pub struct FilterType<I> {
+    inner: I,
+    result_type: Type,
+}
+
+impl<I> Iterator for FilterType<I> where I: Iterator, I::Item: std::borrow::Borrow<Reply> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        use std::borrow::Borrow;
+        let result_type = self.result_type;
+        self.inner.find(|r| r.borrow().types.contains(&result_type))
+    }
+}
+
+/// The envelope of a reply, borrowing its results
+#[derive(Serialize)]
+struct ReplyEnvelope<'a> {
+    results: &'a [Reply],
+    status: &'a StatusCode,
+}
+
+/// Serialize replies and a status into the JSON envelope used by the Google geocoding API
+///
+/// The output can be parsed by this crate, which makes it suitable for mock servers and fixtures.
+pub fn to_reply_json(replies: &[Reply], status: &StatusCode) -> Result<String> {
+    Ok(serde_json::to_string(&ReplyEnvelope { results: replies, status })?)
+}
+
+/// The decoded envelope of a reply from the Google geocoding API, whether or not it was successful
 #[derive(Debug, Deserialize)]
-struct ReplyResult {
-    error_message: Option<String>,
-    results: Vec<Reply>,
+pub struct ReplyResult {
+    /// A more detailed explanation of an unsuccessful status, if the API provided one
+    pub error_message: Option<String>,
+    /// The results, empty unless the status is `Ok`
+    ///
+    /// Some proxies re-wrap replies with a capitalized `Results` key, which is also accepted.
+    #[serde(alias="Results")]
+    pub results: Vec<Reply>,
+    /// Whether the request was successful, and if not why
+    pub status: StatusCode,
+}
+
+impl ReplyResult {
+    /// The results if the status is `Ok`, otherwise an `ApiError` describing the failure
+    pub fn into_results(self) -> Result<Vec<Reply>> {
+        match self {
+            ReplyResult { status: StatusCode::Ok, results, .. } => Ok(results),
+            ReplyResult { status, error_message, .. } => Err(ApiError { status, error_message }.into()),
+        }
+    }
+
+    /// The results along with the status and message if the status is `Ok`, otherwise an `ApiError` describing the failure
+    fn into_replies(self) -> Result<Replies> {
+        match self {
+            ReplyResult { status: StatusCode::Ok, error_message, results } => Ok(Replies { status: StatusCode::Ok, message: error_message, results }),
+            ReplyResult { status, error_message, .. } => Err(ApiError { status, error_message }.into()),
+        }
+    }
+}
+
+/// The results of a query along with the status and message of the reply, see `Connection::geocode_replies`
+#[derive(Debug)]
+pub struct Replies {
     status: StatusCode,
+    message: Option<String>,
+    results: Vec<Reply>,
+}
+
+impl Replies {
+    /// The status of the reply, either `Ok` or `ZeroResults`
+    pub fn status(&self) -> &StatusCode {
+        &self.status
+    }
+
+    /// The message the API sent along with the reply, if any
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// The results, empty unless the status is `Ok`
+    pub fn results(&self) -> &[Reply] {
+        &self.results
+    }
+
+    /// Whether the request succeeded but found nothing
+    pub fn is_zero_results(&self) -> bool {
+        matches!(self.status, StatusCode::ZeroResults)
+    }
+}
+
+/// Iterates over the results, which are empty unless the status is `Ok`
+impl IntoIterator for Replies {
+    type Item = Reply;
+    type IntoIter = std::vec::IntoIter<Reply>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Replies {
+    type Item = &'a Reply;
+    type IntoIter = std::slice::Iter<'a, Reply>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.iter()
+    }
 }
 
 /// Status codes for the geocode API
-#[derive(Debug, Deserialize, Fail)]
+#[derive(Debug, Deserialize, Fail, Serialize)]
 #[serde(rename_all="SCREAMING_SNAKE_CASE")]
 pub enum StatusCode {
     /// Indicates that no errors occurred;
@@ -258,8 +796,78 @@ pub enum StatusCode {
     UnknownError
 }
 
+impl StatusCode {
+    /// Whether the request may succeed if it is sent again
+    ///
+    /// This is the case for `OverQueryLimit` once the rate limit has passed,
+    /// and for `UnknownError`, which indicates a transient server error.
+    /// Note that `OverQueryLimit` also covers an exhausted daily quota, see `ApiError::is_retryable`.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, StatusCode::OverQueryLimit | StatusCode::UnknownError)
+    }
+}
+
+/// An unsuccessful reply from the API
+///
+/// This is the error produced when the API replies with a status other than `Ok`.
+#[derive(Debug)]
+pub struct ApiError {
+    /// Why the request was unsuccessful
+    pub status: StatusCode,
+    /// A more detailed explanation, if the API provided one
+    pub error_message: Option<String>,
+}
+
+impl ApiError {
+    /// Which limit an `OverQueryLimit` reply hit, classified from its error message
+    ///
+    /// Replies that do not mention the daily quota, including HTTP 429 responses, are taken to be rate limited.
+    /// Returns None for any other status.
+    pub fn quota_limit(&self) -> Option<QuotaLimit> {
+        match self.status {
+            StatusCode::OverQueryLimit => match self.error_message {
+                Some(ref message) if message.to_lowercase().contains("daily") => Some(QuotaLimit::DailyQuotaExceeded),
+                _ => Some(QuotaLimit::RateLimited),
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether the request may succeed if it is sent again soon
+    ///
+    /// Unlike `StatusCode::is_retryable`, this is false once the daily quota is exceeded,
+    /// as retrying cannot succeed until the quota resets.
+    pub fn is_retryable(&self) -> bool {
+        self.status.is_retryable() && self.quota_limit() != Some(QuotaLimit::DailyQuotaExceeded)
+    }
+}
+
+/// Which limit an `OverQueryLimit` reply hit, see `ApiError::quota_limit`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum QuotaLimit {
+    /// Too many requests were sent per second, so retrying shortly may succeed
+    RateLimited,
+    /// The daily quota is exhausted, so retrying will fail until it resets
+    DailyQuotaExceeded,
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.error_message {
+            Some(ref message) => write!(f, "{}: {}", self.status, message),
+            None => Display::fmt(&self.status, f),
+        }
+    }
+}
+
+impl Fail for ApiError {
+    fn cause(&self) -> Option<&dyn Fail> {
+        Some(&self.status)
+    }
+}
+
 /// The type of an address (eg street, intersection, etc)
-#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, EnumIter, Eq, Hash, PartialEq, Serialize)]
 #[serde(rename_all="snake_case")]
 pub enum Type {
     /// Indicates a precise street address.
@@ -330,6 +938,26 @@ pub enum Type {
     /// Larger numbers indicate a smaller geographic area.
     Sublocality,
 
+    /// Indicates a first-level civil entity below a locality.
+    #[serde(rename="sublocality_level_1")]
+    SublocalityLevel1,
+
+    /// Indicates a second-level civil entity below a locality.
+    #[serde(rename="sublocality_level_2")]
+    SublocalityLevel2,
+
+    /// Indicates a third-level civil entity below a locality.
+    #[serde(rename="sublocality_level_3")]
+    SublocalityLevel3,
+
+    /// Indicates a fourth-level civil entity below a locality.
+    #[serde(rename="sublocality_level_4")]
+    SublocalityLevel4,
+
+    /// Indicates a fifth-level civil entity below a locality.
+    #[serde(rename="sublocality_level_5")]
+    SublocalityLevel5,
+
     /// Indicates a named neighborhood
     Neighborhood,
 
@@ -394,23 +1022,137 @@ pub enum Type {
 #[derive(Clone,Copy,Debug,Deserialize,Serialize)]
 pub struct Viewport {
     /// Northeast corner of the bounding box
+    #[serde(serialize_with="lat_lng::serialize")]
     pub northeast: Coordinates,
     /// Southwest corner of the bounding box
+    #[serde(serialize_with="lat_lng::serialize")]
     pub southwest: Coordinates,
 }
 
+impl Viewport {
+    /// The WGS-84 equatorial radius, which `nav_types` also uses for great circle distances
+    const EARTH_RADIUS_METERS: f64 = 6_378_137.0;
+
+    /// The smallest viewport containing both points, whichever corners they are
+    ///
+    /// The viewport never crosses the antimeridian, so two points either side of it span most of the globe.
+    pub fn from_corners(a: Coordinates, b: Coordinates) -> Self {
+        let corner = |latitude: f64, longitude: f64| Coordinates(WGS84::new(latitude, longitude, 0f64));
+        Viewport {
+            northeast: corner(a.latitude_degrees().max(b.latitude_degrees()), a.longitude_degrees().max(b.longitude_degrees())),
+            southwest: corner(a.latitude_degrees().min(b.latitude_degrees()), a.longitude_degrees().min(b.longitude_degrees())),
+        }
+    }
+
+    /// A viewport extending `radius_meters` north, south, east and west of `center`
+    ///
+    /// Latitudes are clamped at the poles, and the viewport spans every longitude if it reaches a pole.
+    /// Otherwise it may cross the antimeridian, in which case its west is greater than its east.
+    /// Fails if the radius is negative or not finite.
+    pub fn around(center: Coordinates, radius_meters: f64) -> Result<Self> {
+        if !radius_meters.is_finite() || radius_meters < 0.0 {
+            bail!("Radius {} is not a finite, non-negative distance", radius_meters);
+        }
+        let latitude = center.latitude_degrees();
+        let longitude = center.longitude_degrees();
+        let delta_latitude = (radius_meters / Self::EARTH_RADIUS_METERS).to_degrees().min(180.0);
+        let (north, south) = ((latitude + delta_latitude).min(90.0), (latitude - delta_latitude).max(-90.0));
+        let delta_longitude = delta_latitude / latitude.to_radians().cos();
+        let (east, west) = if north >= 90.0 || south <= -90.0 || delta_longitude >= 180.0 {
+            (180.0, -180.0)
+        } else {
+            (longitude + delta_longitude, longitude - delta_longitude)
+        };
+        Ok(Viewport {
+            northeast: Coordinates::lenient(north, east)?,
+            southwest: Coordinates::lenient(south, west)?,
+        })
+    }
+
+    /// The GeoJSON bounding box of this viewport, as `[west, south, east, north]`
+    ///
+    /// As permitted by GeoJSON, west is greater than east if the viewport crosses the antimeridian.
+    pub fn to_geojson_bbox(&self) -> [f64; 4] {
+        [
+            self.southwest.longitude_degrees(),
+            self.southwest.latitude_degrees(),
+            self.northeast.longitude_degrees(),
+            self.northeast.latitude_degrees(),
+        ]
+    }
+
+    /// The point a fraction `t` of the way along the great circle from the southwest to the northeast corner
+    ///
+    /// `t` of 0 is the southwest corner and 1 the northeast corner.
+    /// Great circles bow towards the poles, so for very large viewports
+    /// points in between may fall slightly outside the viewport's latitudes.
+    pub fn interpolate(&self, t: f64) -> Coordinates {
+        let a = NVector::from(*self.southwest).vector();
+        let b = NVector::from(*self.northeast).vector();
+        let dot = (a.x * b.x + a.y * b.y + a.z * b.z).clamp(-1.0, 1.0);
+        let omega = dot.acos();
+        let (wa, wb) = if omega < 1e-12 {
+            (1.0 - t, t)
+        } else {
+            (((1.0 - t) * omega).sin() / omega.sin(), (t * omega).sin() / omega.sin())
+        };
+        let (x, y, z) = (wa * a.x + wb * b.x, wa * a.y + wb * b.y, wa * a.z + wb * b.z);
+        Coordinates(WGS84::new(
+            z.atan2((x * x + y * y).sqrt()).to_degrees(),
+            y.atan2(x).to_degrees(),
+            0f64,
+        ))
+    }
+
+    /// A GeoJSON `Polygon` geometry tracing the edges of this viewport
+    pub fn to_geojson_polygon(&self) -> serde_json::Value {
+        let [west, south, east, north] = self.to_geojson_bbox();
+        json!({
+            "type": "Polygon",
+            "coordinates": [[[west, south], [east, south], [east, north], [west, north], [west, south]]],
+        })
+    }
+}
+
+/// The smallest viewport containing both points, see `Viewport::from_corners`
+impl From<(Coordinates, Coordinates)> for Viewport {
+    fn from((a, b): (Coordinates, Coordinates)) -> Self {
+        Viewport::from_corners(a, b)
+    }
+}
+
+/// The southwest and northeast corners, in the order the `bounds` parameter takes them
+impl From<Viewport> for (Coordinates, Coordinates) {
+    fn from(v: Viewport) -> Self {
+        (v.southwest, v.northeast)
+    }
+}
+
 /// Language that gets serialized as a language code
 /// 
 /// From https://developers.google.com/maps/faq#languagesupport
-#[derive(Clone,Copy,Debug,EnumIter,Serialize)]
+///
+/// Requires the default `full-enums` feature; `LanguageCode` accepts any language code without it.
+#[cfg(feature="full-enums")]
+#[derive(Clone,Copy,Debug,EnumIter,Eq,Hash,PartialEq,Serialize)]
 #[allow(dead_code)]
 pub enum Language {
+    /// Afrikaans (af)
+    #[serde(rename="af")] Afrikaans,
+    /// Amharic (am)
+    #[serde(rename="am")] Amharic,
     /// Arabic (ar)
     #[serde(rename="ar")] Arabic,
+    /// Azerbaijani (az)
+    #[serde(rename="az")] Azerbaijani,
+    /// Belarusian (be)
+    #[serde(rename="be")] Belarusian,
     /// Bulgarian (bg)
     #[serde(rename="bg")] Bulgarian,
     /// Bengali (bn)
     #[serde(rename="bn")] Bengali,
+    /// Bosnian (bs)
+    #[serde(rename="bs")] Bosnian,
     /// Catalan (ca)
     #[serde(rename="ca")] Catalan,
     /// Czech (cs)
@@ -429,6 +1171,10 @@ pub enum Language {
     #[serde(rename="en-GB")] EnglishGreatBritain,
     /// Spanish (es)
     #[serde(rename="es")] Spanish,
+    /// SpanishLatinAmerica (es-419)
+    #[serde(rename="es-419")] SpanishLatinAmerica,
+    /// Estonian (et)
+    #[serde(rename="et")] Estonian,
     /// Basque (eu)
     #[serde(rename="eu")] Basque,
     /// Farsi (fa)
@@ -439,6 +1185,8 @@ pub enum Language {
     #[serde(rename="fil")] Filipino,
     /// French (fr)
     #[serde(rename="fr")] French,
+    /// FrenchCanada (fr-CA)
+    #[serde(rename="fr-CA")] FrenchCanada,
     /// Galician (gl)
     #[serde(rename="gl")] Galician,
     /// Gujarati (gu)
@@ -449,30 +1197,56 @@ pub enum Language {
     #[serde(rename="hr")] Croatian,
     /// Hungarian (hu)
     #[serde(rename="hu")] Hungarian,
+    /// Armenian (hy)
+    #[serde(rename="hy")] Armenian,
     /// Indonesian (id)
     #[serde(rename="id")] Indonesian,
+    /// Icelandic (is)
+    #[serde(rename="is")] Icelandic,
     /// Italian (it)
     #[serde(rename="it")] Italian,
     /// Hebrew (iw)
     #[serde(rename="iw")] Hebrew,
     /// Japanese (ja)
     #[serde(rename="ja")] Japanese,
+    /// Georgian (ka)
+    #[serde(rename="ka")] Georgian,
+    /// Kazakh (kk)
+    #[serde(rename="kk")] Kazakh,
+    /// Khmer (km)
+    #[serde(rename="km")] Khmer,
     /// Kannada (kn)
     #[serde(rename="kn")] Kannada,
     /// Korean (ko)
     #[serde(rename="ko")] Korean,
+    /// Kyrgyz (ky)
+    #[serde(rename="ky")] Kyrgyz,
+    /// Lao (lo)
+    #[serde(rename="lo")] Lao,
     /// Lithuanian (lt)
     #[serde(rename="lt")] Lithuanian,
     /// Latvian (lv)
     #[serde(rename="lv")] Latvian,
+    /// Macedonian (mk)
+    #[serde(rename="mk")] Macedonian,
     /// Malayalam (ml)
     #[serde(rename="ml")] Malayalam,
+    /// Mongolian (mn)
+    #[serde(rename="mn")] Mongolian,
     /// Marathi (mr)
     #[serde(rename="mr")] Marathi,
+    /// Malay (ms)
+    #[serde(rename="ms")] Malay,
+    /// Burmese (my)
+    #[serde(rename="my")] Burmese,
+    /// Nepali (ne)
+    #[serde(rename="ne")] Nepali,
     /// Dutch (nl)
     #[serde(rename="nl")] Dutch,
     /// Norwegian (no)
     #[serde(rename="no")] Norwegian,
+    /// Punjabi (pa)
+    #[serde(rename="pa")] Punjabi,
     /// Polish (pl)
     #[serde(rename="pl")] Polish,
     /// Portuguese (pt)
@@ -485,14 +1259,20 @@ pub enum Language {
     #[serde(rename="ro")] Romanian,
     /// Russian (ru)
     #[serde(rename="ru")] Russian,
+    /// Sinhalese (si)
+    #[serde(rename="si")] Sinhalese,
     /// Slovak (sk)
     #[serde(rename="sk")] Slovak,
     /// Slovenian (sl)
     #[serde(rename="sl")] Slovenian,
+    /// Albanian (sq)
+    #[serde(rename="sq")] Albanian,
     /// Serbian (sr)
     #[serde(rename="sr")] Serbian,
     /// Swedish (sv)
     #[serde(rename="sv")] Swedish,
+    /// Swahili (sw)
+    #[serde(rename="sw")] Swahili,
     /// Tamil (ta)
     #[serde(rename="ta")] Tamil,
     /// Telugu (te)
@@ -505,529 +1285,546 @@ pub enum Language {
     #[serde(rename="tr")] Turkish,
     /// Ukrainian (uk)
     #[serde(rename="uk")] Ukrainian,
+    /// Urdu (ur)
+    #[serde(rename="ur")] Urdu,
+    /// Uzbek (uz)
+    #[serde(rename="uz")] Uzbek,
     /// Vietnamese (vi)
     #[serde(rename="vi")] Vietnamese,
+    /// Chinese (zh)
+    #[serde(rename="zh")] Chinese,
     /// ChineseSimplified (zh-CN)
     #[serde(rename="zh-CN")] ChineseSimplified,
+    /// ChineseHongKong (zh-HK)
+    #[serde(rename="zh-HK")] ChineseHongKong,
     /// ChineseTraditional (zh-TW)
     #[serde(rename="zh-TW")] ChineseTraditional,
+    /// Zulu (zu)
+    #[serde(rename="zu")] Zulu,
 }
 
 /// Country Code Top-Level Domain
+///
+/// Serialized without the leading dot, eg `fr`, as expected by the `region` parameter.
 /// From https://icannwiki.org/Country_code_top-level_domain
+///
+/// Requires the default `full-enums` feature; `RegionCode` accepts any region code without it.
+#[cfg(feature="full-enums")]
 #[derive(Clone,Copy,Debug,EnumIter,Serialize)]
 #[allow(dead_code)]
 pub enum Region {
     /// AscensionIsland (.ac)
-    #[serde(rename=".ac")] AscensionIsland,
+    #[serde(rename="ac")] AscensionIsland,
     /// Andorra (.ad)
-    #[serde(rename=".ad")] Andorra,
+    #[serde(rename="ad")] Andorra,
     /// UnitedArabEmirates (.ae)
-    #[serde(rename=".ae")] UnitedArabEmirates,
+    #[serde(rename="ae")] UnitedArabEmirates,
     /// Afghanistan (.af)
-    #[serde(rename=".af")] Afghanistan,
+    #[serde(rename="af")] Afghanistan,
     /// AntiguaAndBarbuda (.ag)
-    #[serde(rename=".ag")] AntiguaAndBarbuda,
+    #[serde(rename="ag")] AntiguaAndBarbuda,
     /// Anguilla (.ai)
-    #[serde(rename=".ai")] Anguilla,
+    #[serde(rename="ai")] Anguilla,
     /// Albania (.al)
-    #[serde(rename=".al")] Albania,
+    #[serde(rename="al")] Albania,
     /// Armenia (.am)
-    #[serde(rename=".am")] Armenia,
+    #[serde(rename="am")] Armenia,
     /// AntillesNetherlands (.an)
-    #[serde(rename=".an")] AntillesNetherlands,
+    #[serde(rename="an")] AntillesNetherlands,
     /// Angola (.ao)
-    #[serde(rename=".ao")] Angola,
+    #[serde(rename="ao")] Angola,
     /// Antarctica (.aq)
-    #[serde(rename=".aq")] Antarctica,
+    #[serde(rename="aq")] Antarctica,
     /// Argentina (.ar)
-    #[serde(rename=".ar")] Argentina,
+    #[serde(rename="ar")] Argentina,
     /// AmericanSamoa (.as)
-    #[serde(rename=".as")] AmericanSamoa,
+    #[serde(rename="as")] AmericanSamoa,
     /// Austria (.at)
-    #[serde(rename=".at")] Austria,
+    #[serde(rename="at")] Austria,
     /// Australia (.au)
-    #[serde(rename=".au")] Australia,
+    #[serde(rename="au")] Australia,
     /// Aruba (.aw)
-    #[serde(rename=".aw")] Aruba,
+    #[serde(rename="aw")] Aruba,
     /// AlandIslands (.ax)
-    #[serde(rename=".ax")] AlandIslands,
+    #[serde(rename="ax")] AlandIslands,
     /// Azerbaijan (.az)
-    #[serde(rename=".az")] Azerbaijan,
+    #[serde(rename="az")] Azerbaijan,
     /// BosniaAndHerzegovina (.ba)
-    #[serde(rename=".ba")] BosniaAndHerzegovina,
+    #[serde(rename="ba")] BosniaAndHerzegovina,
     /// Barbados (.bb)
-    #[serde(rename=".bb")] Barbados,
+    #[serde(rename="bb")] Barbados,
     /// Bangladesh (.bd)
-    #[serde(rename=".bd")] Bangladesh,
+    #[serde(rename="bd")] Bangladesh,
     /// Belgium (.be)
-    #[serde(rename=".be")] Belgium,
+    #[serde(rename="be")] Belgium,
     /// BurkinaFaso (.bf)
-    #[serde(rename=".bf")] BurkinaFaso,
+    #[serde(rename="bf")] BurkinaFaso,
     /// Bulgaria (.bg)
-    #[serde(rename=".bg")] Bulgaria,
+    #[serde(rename="bg")] Bulgaria,
     /// Bahrain (.bh)
-    #[serde(rename=".bh")] Bahrain,
+    #[serde(rename="bh")] Bahrain,
     /// Burundi (.bi)
-    #[serde(rename=".bi")] Burundi,
+    #[serde(rename="bi")] Burundi,
     /// Benin (.bj)
-    #[serde(rename=".bj")] Benin,
+    #[serde(rename="bj")] Benin,
     /// SaintBarthelemy (.bl)
-    #[serde(rename=".bl")] SaintBarthelemy,
+    #[serde(rename="bl")] SaintBarthelemy,
     /// Bermuda (.bm)
-    #[serde(rename=".bm")] Bermuda,
+    #[serde(rename="bm")] Bermuda,
     /// BruneiDarussalam (.bn)
-    #[serde(rename=".bn")] BruneiDarussalam,
+    #[serde(rename="bn")] BruneiDarussalam,
     /// Bolivia (.bo)
-    #[serde(rename=".bo")] Bolivia,
-    /// Bonaire (.bq)
-    #[serde(rename=".bq")] BonaireSintEustatiusAndSaba,
+    #[serde(rename="bo")] Bolivia,
+    /// BonaireSintEustatiusAndSaba (.bq)
+    #[serde(rename="bq")] BonaireSintEustatiusAndSaba,
     /// Brazil (.br)
-    #[serde(rename=".br")] Brazil,
+    #[serde(rename="br")] Brazil,
     /// Bahamas (.bs)
-    #[serde(rename=".bs")] Bahamas,
+    #[serde(rename="bs")] Bahamas,
     /// Bhutan (.bt)
-    #[serde(rename=".bt")] Bhutan,
+    #[serde(rename="bt")] Bhutan,
     /// BouvetIsland (.bv)
-    #[serde(rename=".bv")] BouvetIsland,
+    #[serde(rename="bv")] BouvetIsland,
     /// Botswana (.bw)
-    #[serde(rename=".bw")] Botswana,
+    #[serde(rename="bw")] Botswana,
     /// Belarus (.by)
-    #[serde(rename=".by")] Belarus,
+    #[serde(rename="by")] Belarus,
     /// Belize (.bz)
-    #[serde(rename=".bz")] Belize,
+    #[serde(rename="bz")] Belize,
     /// Canada (.ca)
-    #[serde(rename=".ca")] Canada,
+    #[serde(rename="ca")] Canada,
     /// CocosIslands (.cc)
-    #[serde(rename=".cc")] CocosIslands,
+    #[serde(rename="cc")] CocosIslands,
     /// DemocraticRepublicOfTheCongo (.cd)
-    #[serde(rename=".cd")] DemocraticRepublicOfTheCongo,
+    #[serde(rename="cd")] DemocraticRepublicOfTheCongo,
     /// CentralAfricanRepublic (.cf)
-    #[serde(rename=".cf")] CentralAfricanRepublic,
+    #[serde(rename="cf")] CentralAfricanRepublic,
     /// RepublicOfCongo (.cg)
-    #[serde(rename=".cg")] RepublicOfCongo,
+    #[serde(rename="cg")] RepublicOfCongo,
     /// Switzerland (.ch)
-    #[serde(rename=".ch")] Switzerland,
+    #[serde(rename="ch")] Switzerland,
     /// CoteDivoire (.ci)
-    #[serde(rename=".ci")] CoteDivoire,
+    #[serde(rename="ci")] CoteDivoire,
     /// CookIslands (.ck)
-    #[serde(rename=".ck")] CookIslands,
+    #[serde(rename="ck")] CookIslands,
     /// Chile (.cl)
-    #[serde(rename=".cl")] Chile,
+    #[serde(rename="cl")] Chile,
     /// Cameroon (.cm)
-    #[serde(rename=".cm")] Cameroon,
+    #[serde(rename="cm")] Cameroon,
     /// China (.cn)
-    #[serde(rename=".cn")] China,
+    #[serde(rename="cn")] China,
     /// Colombia (.co)
-    #[serde(rename=".co")] Colombia,
+    #[serde(rename="co")] Colombia,
     /// CostaRica (.cr)
-    #[serde(rename=".cr")] CostaRica,
+    #[serde(rename="cr")] CostaRica,
     /// Cuba (.cu)
-    #[serde(rename=".cu")] Cuba,
+    #[serde(rename="cu")] Cuba,
     /// CapeVerde (.cv)
-    #[serde(rename=".cv")] CapeVerde,
+    #[serde(rename="cv")] CapeVerde,
     /// Curacao (.cw)
-    #[serde(rename=".cw")] Curacao,
+    #[serde(rename="cw")] Curacao,
     /// ChristmasIsland (.cx)
-    #[serde(rename=".cx")] ChristmasIsland,
+    #[serde(rename="cx")] ChristmasIsland,
     /// Cyprus (.cy)
-    #[serde(rename=".cy")] Cyprus,
+    #[serde(rename="cy")] Cyprus,
     /// CzechRepublic (.cz)
-    #[serde(rename=".cz")] CzechRepublic,
+    #[serde(rename="cz")] CzechRepublic,
     /// Germany (.de)
-    #[serde(rename=".de")] Germany,
+    #[serde(rename="de")] Germany,
     /// Djibouti (.dj)
-    #[serde(rename=".dj")] Djibouti,
+    #[serde(rename="dj")] Djibouti,
     /// Denmark (.dk)
-    #[serde(rename=".dk")] Denmark,
+    #[serde(rename="dk")] Denmark,
     /// Dominica (.dm)
-    #[serde(rename=".dm")] Dominica,
+    #[serde(rename="dm")] Dominica,
     /// DominicanRepublic (.do)
-    #[serde(rename=".do")] DominicanRepublic,
+    #[serde(rename="do")] DominicanRepublic,
     /// Algeria (.dz)
-    #[serde(rename=".dz")] Algeria,
+    #[serde(rename="dz")] Algeria,
     /// Ecuador (.ec)
-    #[serde(rename=".ec")] Ecuador,
+    #[serde(rename="ec")] Ecuador,
     /// Estonia (.ee)
-    #[serde(rename=".ee")] Estonia,
+    #[serde(rename="ee")] Estonia,
     /// Egypt (.eg)
-    #[serde(rename=".eg")] Egypt,
+    #[serde(rename="eg")] Egypt,
     /// WesternSahara (.eh)
-    #[serde(rename=".eh")] WesternSahara,
+    #[serde(rename="eh")] WesternSahara,
     /// Eritrea (.er)
-    #[serde(rename=".er")] Eritrea,
+    #[serde(rename="er")] Eritrea,
     /// Spain (.es)
-    #[serde(rename=".es")] Spain,
+    #[serde(rename="es")] Spain,
     /// Ethiopia (.et)
-    #[serde(rename=".et")] Ethiopia,
+    #[serde(rename="et")] Ethiopia,
     /// EuropeanUnion (.eu)
-    #[serde(rename=".eu")] EuropeanUnion,
+    #[serde(rename="eu")] EuropeanUnion,
     /// Finland (.fi)
-    #[serde(rename=".fi")] Finland,
+    #[serde(rename="fi")] Finland,
     /// Fiji (.fj)
-    #[serde(rename=".fj")] Fiji,
+    #[serde(rename="fj")] Fiji,
     /// FalklandIslands (.fk)
-    #[serde(rename=".fk")] FalklandIslands,
+    #[serde(rename="fk")] FalklandIslands,
     /// FederatedStatesOfMicronesia (.fm)
-    #[serde(rename=".fm")] FederatedStatesOfMicronesia,
+    #[serde(rename="fm")] FederatedStatesOfMicronesia,
     /// FaroeIslands (.fo)
-    #[serde(rename=".fo")] FaroeIslands,
+    #[serde(rename="fo")] FaroeIslands,
     /// France (.fr)
-    #[serde(rename=".fr")] France,
+    #[serde(rename="fr")] France,
     /// Gabon (.ga)
-    #[serde(rename=".ga")] Gabon,
+    #[serde(rename="ga")] Gabon,
     /// Grenada (.gd)
-    #[serde(rename=".gd")] Grenada,
+    #[serde(rename="gd")] Grenada,
     /// Georgia (.ge)
-    #[serde(rename=".ge")] Georgia,
+    #[serde(rename="ge")] Georgia,
     /// FrenchGuiana (.gf)
-    #[serde(rename=".gf")] FrenchGuiana,
+    #[serde(rename="gf")] FrenchGuiana,
     /// Guernsey (.gg)
-    #[serde(rename=".gg")] Guernsey,
+    #[serde(rename="gg")] Guernsey,
     /// Ghana (.gh)
-    #[serde(rename=".gh")] Ghana,
+    #[serde(rename="gh")] Ghana,
     /// Gibraltar (.gi)
-    #[serde(rename=".gi")] Gibraltar,
+    #[serde(rename="gi")] Gibraltar,
     /// Greenland (.gl)
-    #[serde(rename=".gl")] Greenland,
+    #[serde(rename="gl")] Greenland,
     /// Gambia (.gm)
-    #[serde(rename=".gm")] Gambia,
+    #[serde(rename="gm")] Gambia,
     /// Guinea (.gn)
-    #[serde(rename=".gn")] Guinea,
+    #[serde(rename="gn")] Guinea,
     /// Guadeloupe (.gp)
-    #[serde(rename=".gp")] Guadeloupe,
+    #[serde(rename="gp")] Guadeloupe,
     /// EquatorialGuinea (.gq)
-    #[serde(rename=".gq")] EquatorialGuinea,
+    #[serde(rename="gq")] EquatorialGuinea,
     /// Greece (.gr)
-    #[serde(rename=".gr")] Greece,
+    #[serde(rename="gr")] Greece,
     /// SouthGeorgiaAndTheSouthSandwichIslands (.gs)
-    #[serde(rename=".gs")] SouthGeorgiaAndTheSouthSandwichIslands,
+    #[serde(rename="gs")] SouthGeorgiaAndTheSouthSandwichIslands,
     /// Guatemala (.gt)
-    #[serde(rename=".gt")] Guatemala,
+    #[serde(rename="gt")] Guatemala,
     /// Guam (.gu)
-    #[serde(rename=".gu")] Guam,
+    #[serde(rename="gu")] Guam,
     /// GuineaBissau (.gw)
-    #[serde(rename=".gw")] GuineaBissau,
+    #[serde(rename="gw")] GuineaBissau,
     /// Guyana (.gy)
-    #[serde(rename=".gy")] Guyana,
+    #[serde(rename="gy")] Guyana,
     /// HongKong (.hk)
-    #[serde(rename=".hk")] HongKong,
+    #[serde(rename="hk")] HongKong,
     /// HeardIslandAndMcDonaldIslands (.hm)
-    #[serde(rename=".hm")] HeardIslandAndMcDonaldIslands,
+    #[serde(rename="hm")] HeardIslandAndMcDonaldIslands,
     /// Honduras (.hn)
-    #[serde(rename=".hn")] Honduras,
+    #[serde(rename="hn")] Honduras,
     /// Croatia (.hr)
-    #[serde(rename=".hr")] Croatia,
+    #[serde(rename="hr")] Croatia,
     /// Haiti (.ht)
-    #[serde(rename=".ht")] Haiti,
+    #[serde(rename="ht")] Haiti,
     /// Hungary (.hu)
-    #[serde(rename=".hu")] Hungary,
+    #[serde(rename="hu")] Hungary,
     /// Indonesia (.id)
-    #[serde(rename=".id")] Indonesia,
+    #[serde(rename="id")] Indonesia,
     /// Ireland (.ie)
-    #[serde(rename=".ie")] Ireland,
+    #[serde(rename="ie")] Ireland,
     /// Israel (.il)
-    #[serde(rename=".il")] Israel,
+    #[serde(rename="il")] Israel,
     /// IsleOfMan (.im)
-    #[serde(rename=".im")] IsleOfMan,
+    #[serde(rename="im")] IsleOfMan,
     /// India (.in)
-    #[serde(rename=".in")] India,
+    #[serde(rename="in")] India,
     /// BritishIndianOceanTerritory (.io)
-    #[serde(rename=".io")] BritishIndianOceanTerritory,
+    #[serde(rename="io")] BritishIndianOceanTerritory,
     /// Iraq (.iq)
-    #[serde(rename=".iq")] Iraq,
+    #[serde(rename="iq")] Iraq,
     /// IslamicRepublicOfIran (.ir)
-    #[serde(rename=".ir")] IslamicRepublicOfIran,
+    #[serde(rename="ir")] IslamicRepublicOfIran,
     /// Iceland (.is)
-    #[serde(rename=".is")] Iceland,
+    #[serde(rename="is")] Iceland,
     /// Italy (.it)
-    #[serde(rename=".it")] Italy,
+    #[serde(rename="it")] Italy,
     /// Jersey (.je)
-    #[serde(rename=".je")] Jersey,
+    #[serde(rename="je")] Jersey,
     /// Jamaica (.jm)
-    #[serde(rename=".jm")] Jamaica,
+    #[serde(rename="jm")] Jamaica,
     /// Jordan (.jo)
-    #[serde(rename=".jo")] Jordan,
+    #[serde(rename="jo")] Jordan,
     /// Japan (.jp)
-    #[serde(rename=".jp")] Japan,
+    #[serde(rename="jp")] Japan,
     /// Kenya (.ke)
-    #[serde(rename=".ke")] Kenya,
+    #[serde(rename="ke")] Kenya,
     /// Kyrgyzstan (.kg)
-    #[serde(rename=".kg")] Kyrgyzstan,
+    #[serde(rename="kg")] Kyrgyzstan,
     /// Cambodia (.kh)
-    #[serde(rename=".kh")] Cambodia,
+    #[serde(rename="kh")] Cambodia,
     /// Kiribati (.ki)
-    #[serde(rename=".ki")] Kiribati,
+    #[serde(rename="ki")] Kiribati,
     /// Comoros (.km)
-    #[serde(rename=".km")] Comoros,
+    #[serde(rename="km")] Comoros,
     /// SaintKittsAndNevis (.kn)
-    #[serde(rename=".kn")] SaintKittsAndNevis,
+    #[serde(rename="kn")] SaintKittsAndNevis,
     /// DemocraticPeoplesRepublicOfKorea (.kp)
-    #[serde(rename=".kp")] DemocraticPeoplesRepublicOfKorea,
-    /// RepublicOfKorea (.kp)
-    #[serde(rename=".kp")] RepublicOfKorea,
+    #[serde(rename="kp")] DemocraticPeoplesRepublicOfKorea,
+    /// RepublicOfKorea (.kr)
+    #[serde(rename="kr")] RepublicOfKorea,
     /// Kuwait (.kw)
-    #[serde(rename=".kw")] Kuwait,
+    #[serde(rename="kw")] Kuwait,
     /// CaymenIslands (.ky)
-    #[serde(rename=".ky")] CaymenIslands,
+    #[serde(rename="ky")] CaymenIslands,
     /// Kazakhstan (.kz)
-    #[serde(rename=".kz")] Kazakhstan,
+    #[serde(rename="kz")] Kazakhstan,
     /// Laos (.la)
-    #[serde(rename=".la")] Laos,
+    #[serde(rename="la")] Laos,
     /// Lebanon (.lb)
-    #[serde(rename=".lb")] Lebanon,
+    #[serde(rename="lb")] Lebanon,
     /// SaintLucia (.lc)
-    #[serde(rename=".lc")] SaintLucia,
+    #[serde(rename="lc")] SaintLucia,
     /// Liechtenstein (.li)
-    #[serde(rename=".li")] Liechtenstein,
+    #[serde(rename="li")] Liechtenstein,
     /// SriLanka (.lk)
-    #[serde(rename=".lk")] SriLanka,
+    #[serde(rename="lk")] SriLanka,
     /// Liberia (.lr)
-    #[serde(rename=".lr")] Liberia,
+    #[serde(rename="lr")] Liberia,
     /// Lesotho (.ls)
-    #[serde(rename=".ls")] Lesotho,
+    #[serde(rename="ls")] Lesotho,
     /// Lithuania (.lt)
-    #[serde(rename=".lt")] Lithuania,
+    #[serde(rename="lt")] Lithuania,
     /// Luxembourg (.lu)
-    #[serde(rename=".lu")] Luxembourg,
+    #[serde(rename="lu")] Luxembourg,
     /// Latvia (.lv)
-    #[serde(rename=".lv")] Latvia,
+    #[serde(rename="lv")] Latvia,
     /// Libya (.ly)
-    #[serde(rename=".ly")] Libya,
+    #[serde(rename="ly")] Libya,
     /// Morocco (.ma)
-    #[serde(rename=".ma")] Morocco,
+    #[serde(rename="ma")] Morocco,
     /// Monaco (.mc)
-    #[serde(rename=".mc")] Monaco,
+    #[serde(rename="mc")] Monaco,
     /// RepublicOfMoldova (.md)
-    #[serde(rename=".md")] RepublicOfMoldova,
+    #[serde(rename="md")] RepublicOfMoldova,
     /// Montenegro (.me)
-    #[serde(rename=".me")] Montenegro,
+    #[serde(rename="me")] Montenegro,
     /// SaintMartin (.mf)
-    #[serde(rename=".mf")] SaintMartin,
+    #[serde(rename="mf")] SaintMartin,
     /// Madagascar (.mg)
-    #[serde(rename=".mg")] Madagascar,
+    #[serde(rename="mg")] Madagascar,
     /// MarshallIslands (.mh)
-    #[serde(rename=".mh")] MarshallIslands,
+    #[serde(rename="mh")] MarshallIslands,
     /// Macedonia (.mk)
-    #[serde(rename=".mk")] Macedonia,
+    #[serde(rename="mk")] Macedonia,
     /// Mali (.ml)
-    #[serde(rename=".ml")] Mali,
+    #[serde(rename="ml")] Mali,
     /// Myanmar (.mm)
-    #[serde(rename=".mm")] Myanmar,
+    #[serde(rename="mm")] Myanmar,
     /// Mongolia (.mn)
-    #[serde(rename=".mn")] Mongolia,
+    #[serde(rename="mn")] Mongolia,
     /// Macao (.mo)
-    #[serde(rename=".mo")] Macao,
+    #[serde(rename="mo")] Macao,
     /// NorthernMarianaIslands (.mp)
-    #[serde(rename=".mp")] NorthernMarianaIslands,
+    #[serde(rename="mp")] NorthernMarianaIslands,
     /// Martinique (.mq)
-    #[serde(rename=".mq")] Martinique,
+    #[serde(rename="mq")] Martinique,
     /// Mauritania (.mr)
-    #[serde(rename=".mr")] Mauritania,
+    #[serde(rename="mr")] Mauritania,
     /// Montserrat (.ms)
-    #[serde(rename=".ms")] Montserrat,
+    #[serde(rename="ms")] Montserrat,
     /// Malta (.mt)
-    #[serde(rename=".mt")] Malta,
+    #[serde(rename="mt")] Malta,
     /// Mauritius (.mu)
-    #[serde(rename=".mu")] Mauritius,
+    #[serde(rename="mu")] Mauritius,
     /// Maldives (.mv)
-    #[serde(rename=".mv")] Maldives,
+    #[serde(rename="mv")] Maldives,
     /// Malawi (.mw)
-    #[serde(rename=".mw")] Malawi,
+    #[serde(rename="mw")] Malawi,
     /// Mexico (.mx)
-    #[serde(rename=".mx")] Mexico,
+    #[serde(rename="mx")] Mexico,
     /// Malaysia (.my)
-    #[serde(rename=".my")] Malaysia,
+    #[serde(rename="my")] Malaysia,
     /// Mozambique (.mz)
-    #[serde(rename=".mz")] Mozambique,
+    #[serde(rename="mz")] Mozambique,
     /// Namibia (.na)
-    #[serde(rename=".na")] Namibia,
+    #[serde(rename="na")] Namibia,
     /// NewCaledonia (.nc)
-    #[serde(rename=".nc")] NewCaledonia,
+    #[serde(rename="nc")] NewCaledonia,
     /// Niger (.ne)
-    #[serde(rename=".ne")] Niger,
+    #[serde(rename="ne")] Niger,
     /// NorfolkIsland (.nf)
-    #[serde(rename=".nf")] NorfolkIsland,
+    #[serde(rename="nf")] NorfolkIsland,
     /// Nigeria (.ng)
-    #[serde(rename=".ng")] Nigeria,
+    #[serde(rename="ng")] Nigeria,
     /// Nicaragua (.ni)
-    #[serde(rename=".ni")] Nicaragua,
+    #[serde(rename="ni")] Nicaragua,
     /// Netherlands (.nl)
-    #[serde(rename=".nl")] Netherlands,
+    #[serde(rename="nl")] Netherlands,
     /// Norway (.no)
-    #[serde(rename=".no")] Norway,
+    #[serde(rename="no")] Norway,
     /// Nepal (.np)
-    #[serde(rename=".np")] Nepal,
+    #[serde(rename="np")] Nepal,
     /// Nauru (.nr)
-    #[serde(rename=".nr")] Nauru,
+    #[serde(rename="nr")] Nauru,
     /// Niue (.nu)
-    #[serde(rename=".nu")] Niue,
+    #[serde(rename="nu")] Niue,
     /// NewZealand (.nz)
-    #[serde(rename=".nz")] NewZealand,
+    #[serde(rename="nz")] NewZealand,
     /// Oman (.om)
-    #[serde(rename=".om")] Oman,
+    #[serde(rename="om")] Oman,
     /// Panama (.pa)
-    #[serde(rename=".pa")] Panama,
+    #[serde(rename="pa")] Panama,
     /// Peru (.pe)
-    #[serde(rename=".pe")] Peru,
+    #[serde(rename="pe")] Peru,
     /// FrenchPolynesia (.pf)
-    #[serde(rename=".pf")] FrenchPolynesia,
+    #[serde(rename="pf")] FrenchPolynesia,
     /// PapuaNewGuinea (.pg)
-    #[serde(rename=".pg")] PapuaNewGuinea,
+    #[serde(rename="pg")] PapuaNewGuinea,
     /// Philippines (.ph)
-    #[serde(rename=".ph")] Philippines,
+    #[serde(rename="ph")] Philippines,
     /// Pakistan (.pk)
-    #[serde(rename=".pk")] Pakistan,
+    #[serde(rename="pk")] Pakistan,
     /// Poland (.pl)
-    #[serde(rename=".pl")] Poland,
+    #[serde(rename="pl")] Poland,
     /// SaintPierreAndMiquelon (.pm)
-    #[serde(rename=".pm")] SaintPierreAndMiquelon,
+    #[serde(rename="pm")] SaintPierreAndMiquelon,
     /// Pitcairn (.pn)
-    #[serde(rename=".pn")] Pitcairn,
+    #[serde(rename="pn")] Pitcairn,
     /// PuertoRico (.pr)
-    #[serde(rename=".pr")] PuertoRico,
+    #[serde(rename="pr")] PuertoRico,
     /// Palestine (.ps)
-    #[serde(rename=".ps")] Palestine,
+    #[serde(rename="ps")] Palestine,
     /// Portugal (.pt)
-    #[serde(rename=".pt")] Portugal,
+    #[serde(rename="pt")] Portugal,
     /// Palau (.pw)
-    #[serde(rename=".pw")] Palau,
+    #[serde(rename="pw")] Palau,
     /// Paraguay (.py)
-    #[serde(rename=".py")] Paraguay,
+    #[serde(rename="py")] Paraguay,
     /// Qatar (.qa)
-    #[serde(rename=".qa")] Qatar,
+    #[serde(rename="qa")] Qatar,
     /// Reunion (.re)
-    #[serde(rename=".re")] Reunion,
+    #[serde(rename="re")] Reunion,
     /// Romania (.ro)
-    #[serde(rename=".ro")] Romania,
+    #[serde(rename="ro")] Romania,
     /// Serbia (.rs)
-    #[serde(rename=".rs")] Serbia,
+    #[serde(rename="rs")] Serbia,
     /// Russia (.ru)
-    #[serde(rename=".ru")] Russia,
+    #[serde(rename="ru")] Russia,
     /// Rwanda (.rw)
-    #[serde(rename=".rw")] Rwanda,
+    #[serde(rename="rw")] Rwanda,
     /// SaudiArabia (.sa)
-    #[serde(rename=".sa")] SaudiArabia,
+    #[serde(rename="sa")] SaudiArabia,
     /// SolomonIslands (.sb)
-    #[serde(rename=".sb")] SolomonIslands,
+    #[serde(rename="sb")] SolomonIslands,
     /// Seychelles (.sc)
-    #[serde(rename=".sc")] Seychelles,
+    #[serde(rename="sc")] Seychelles,
     /// Sudan (.sd)
-    #[serde(rename=".sd")] Sudan,
+    #[serde(rename="sd")] Sudan,
     /// Sweden (.se)
-    #[serde(rename=".se")] Sweden,
+    #[serde(rename="se")] Sweden,
     /// Singapore (.sg)
-    #[serde(rename=".sg")] Singapore,
+    #[serde(rename="sg")] Singapore,
     /// SaintHelena (.sh)
-    #[serde(rename=".sh")] SaintHelena,
+    #[serde(rename="sh")] SaintHelena,
     /// Slovenia (.si)
-    #[serde(rename=".si")] Slovenia,
+    #[serde(rename="si")] Slovenia,
     /// SvalbardAndJanMayen (.sj)
-    #[serde(rename=".sj")] SvalbardAndJanMayen,
+    #[serde(rename="sj")] SvalbardAndJanMayen,
     /// Slovakia (.sk)
-    #[serde(rename=".sk")] Slovakia,
+    #[serde(rename="sk")] Slovakia,
     /// SierraLeone (.sl)
-    #[serde(rename=".sl")] SierraLeone,
+    #[serde(rename="sl")] SierraLeone,
     /// SanMarino (.sm)
-    #[serde(rename=".sm")] SanMarino,
+    #[serde(rename="sm")] SanMarino,
     /// Senegal (.sn)
-    #[serde(rename=".sn")] Senegal,
+    #[serde(rename="sn")] Senegal,
     /// Somalia (.so)
-    #[serde(rename=".so")] Somalia,
+    #[serde(rename="so")] Somalia,
     /// Suriname (.sr)
-    #[serde(rename=".sr")] Suriname,
+    #[serde(rename="sr")] Suriname,
     /// SouthSudan (.ss)
-    #[serde(rename=".ss")] SouthSudan,
+    #[serde(rename="ss")] SouthSudan,
     /// SaoTomeAndPrincipe (.st)
-    #[serde(rename=".st")] SaoTomeAndPrincipe,
+    #[serde(rename="st")] SaoTomeAndPrincipe,
     /// SovietUnion (.su)
-    #[serde(rename=".su")] SovietUnion,
+    #[serde(rename="su")] SovietUnion,
     /// ElSalvador (.sv)
-    #[serde(rename=".sv")] ElSalvador,
+    #[serde(rename="sv")] ElSalvador,
     /// SintMaarten (.sx)
-    #[serde(rename=".sx")] SintMaarten,
+    #[serde(rename="sx")] SintMaarten,
     /// Syria (.sy)
-    #[serde(rename=".sy")] Syria,
+    #[serde(rename="sy")] Syria,
     /// Swaziland (.sz)
-    #[serde(rename=".sz")] Swaziland,
+    #[serde(rename="sz")] Swaziland,
     /// TurksAndCaicosIslands (.tc)
-    #[serde(rename=".tc")] TurksAndCaicosIslands,
+    #[serde(rename="tc")] TurksAndCaicosIslands,
     /// Chad (.td)
-    #[serde(rename=".td")] Chad,
+    #[serde(rename="td")] Chad,
     /// FrenchSouthernTerritories (.tf)
-    #[serde(rename=".tf")] FrenchSouthernTerritories,
+    #[serde(rename="tf")] FrenchSouthernTerritories,
     /// Togo (.tg)
-    #[serde(rename=".tg")] Togo,
+    #[serde(rename="tg")] Togo,
     /// Thailand (.th)
-    #[serde(rename=".th")] Thailand,
+    #[serde(rename="th")] Thailand,
     /// Tajikistan (.tj)
-    #[serde(rename=".tj")] Tajikistan,
+    #[serde(rename="tj")] Tajikistan,
     /// Tokelau (.tk)
-    #[serde(rename=".tk")] Tokelau,
+    #[serde(rename="tk")] Tokelau,
     /// TimorLeste (.tl)
-    #[serde(rename=".tl")] TimorLeste,
+    #[serde(rename="tl")] TimorLeste,
     /// Turkmenistan (.tm)
-    #[serde(rename=".tm")] Turkmenistan,
+    #[serde(rename="tm")] Turkmenistan,
     /// Tunisia (.tn)
-    #[serde(rename=".tn")] Tunisia,
+    #[serde(rename="tn")] Tunisia,
     /// Tonga (.to)
-    #[serde(rename=".to")] Tonga,
+    #[serde(rename="to")] Tonga,
     /// PortugueseTimor (.tp)
-    #[serde(rename=".tp")] PortugueseTimor,
+    #[serde(rename="tp")] PortugueseTimor,
     /// Turkey (.tr)
-    #[serde(rename=".tr")] Turkey,
+    #[serde(rename="tr")] Turkey,
     /// TrinidadAndTobago (.tt)
-    #[serde(rename=".tt")] TrinidadAndTobago,
+    #[serde(rename="tt")] TrinidadAndTobago,
     /// Tuvalu (.tv)
-    #[serde(rename=".tv")] Tuvalu,
+    #[serde(rename="tv")] Tuvalu,
     /// Taiwan (.tw)
-    #[serde(rename=".tw")] Taiwan,
+    #[serde(rename="tw")] Taiwan,
     /// Tanzania (.tz)
-    #[serde(rename=".tz")] Tanzania,
+    #[serde(rename="tz")] Tanzania,
     /// Ukraine (.ua)
-    #[serde(rename=".ua")] Ukraine,
+    #[serde(rename="ua")] Ukraine,
     /// Uganda (.ug)
-    #[serde(rename=".ug")] Uganda,
+    #[serde(rename="ug")] Uganda,
     /// UnitedKingdom (.uk)
-    #[serde(rename=".uk")] UnitedKingdom,
+    #[serde(rename="uk")] UnitedKingdom,
     /// UnitedStatesMinorOutlyingIslands (.um)
-    #[serde(rename=".um")] UnitedStatesMinorOutlyingIslands,
+    #[serde(rename="um")] UnitedStatesMinorOutlyingIslands,
     /// UnitedStates (.us)
-    #[serde(rename=".us")] UnitedStates,
+    #[serde(rename="us")] UnitedStates,
     /// Uruguay (.uy)
-    #[serde(rename=".uy")] Uruguay,
+    #[serde(rename="uy")] Uruguay,
     /// Uzbekistan (.uz)
-    #[serde(rename=".uz")] Uzbekistan,
+    #[serde(rename="uz")] Uzbekistan,
     /// VaticanCity (.va)
-    #[serde(rename=".va")] VaticanCity,
+    #[serde(rename="va")] VaticanCity,
     /// SaintVincentAndTheGrenadines (.vc)
-    #[serde(rename=".vc")] SaintVincentAndTheGrenadines,
+    #[serde(rename="vc")] SaintVincentAndTheGrenadines,
     /// Venezuela (.ve)
-    #[serde(rename=".ve")] Venezuela,
+    #[serde(rename="ve")] Venezuela,
     /// BritishVirginIslands (.vg)
-    #[serde(rename=".vg")] BritishVirginIslands,
+    #[serde(rename="vg")] BritishVirginIslands,
     /// USVirginIslands (.vi)
-    #[serde(rename=".vi")] USVirginIslands,
+    #[serde(rename="vi")] USVirginIslands,
     /// Vietnam (.vn)
-    #[serde(rename=".vn")] Vietnam,
+    #[serde(rename="vn")] Vietnam,
     /// Vanuatu (.vu)
-    #[serde(rename=".vu")] Vanuatu,
+    #[serde(rename="vu")] Vanuatu,
     /// WallisAndFutuna (.wf)
-    #[serde(rename=".wf")] WallisAndFutuna,
+    #[serde(rename="wf")] WallisAndFutuna,
     /// Samoa (.ws)
-    #[serde(rename=".ws")] Samoa,
+    #[serde(rename="ws")] Samoa,
+    /// Yemen (.ye)
+    #[serde(rename="ye")] Yemen,
     /// Mayote (.yt)
-    #[serde(rename=".yt")] Mayote,
+    #[serde(rename="yt")] Mayote,
     /// SouthAfrica (.za)
-    #[serde(rename=".za")] SouthAfrica,
+    #[serde(rename="za")] SouthAfrica,
     /// Zambia (.zm)
-    #[serde(rename=".zm")] Zambia,
+    #[serde(rename="zm")] Zambia,
     /// Zimbabwe (.zw)
-    #[serde(rename=".zw")] Zimbabwe,
+    #[serde(rename="zw")] Zimbabwe,
 }
 
 /// A rule for a component filter
-#[derive(Debug,Eq,Hash,PartialEq)]
+#[derive(Clone,Debug,Eq,Hash,PartialEq)]
 pub enum ComponentFilterRule {
     /// Matches postal_code and postal_code_prefix.
     PostalCode(String),
@@ -1041,358 +1838,2806 @@ pub enum ComponentFilterRule {
     AdministrativeArea(String),
 }
 
-impl Serialize for ComponentFilterRule {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
-        let v = match self {
-            ComponentFilterRule::PostalCode(x)=>x,
-            ComponentFilterRule::Country(x)=>x,
-            ComponentFilterRule::Route(x)=>x,
-            ComponentFilterRule::Locality(x)=>x,
-            ComponentFilterRule::AdministrativeArea(x)=>x,
-        };
-        serializer.serialize_str(&format!("{}:{}", serde_util::variant_name(self), v))
+impl ComponentFilterRule {
+    /// Creates a validated administrative area rule
+    pub fn administrative_area(value: impl Into<String>) -> std::result::Result<Self, InvalidComponent> {
+        Self::validated(ComponentFilterRule::AdministrativeArea(value.into()))
     }
-}
 
-pub(crate) trait ApiQuery : Debug + Serialize {
-}
+    /// Creates a validated country rule, see `validate`
+    pub fn country(value: impl Into<String>) -> std::result::Result<Self, InvalidComponent> {
+        Self::validated(ComponentFilterRule::Country(value.into()))
+    }
 
-/// Represents a connection to the Google geocoding API
-pub struct Connection {
-    client: Client,
-}
+    /// Creates a validated locality rule
+    pub fn locality(value: impl Into<String>) -> std::result::Result<Self, InvalidComponent> {
+        Self::validated(ComponentFilterRule::Locality(value.into()))
+    }
 
-impl Connection {
-    const URL: &'static str = "https://maps.google.com/maps/api/geocode/json";
+    /// Creates a validated postal code rule
+    pub fn postal_code(value: impl Into<String>) -> std::result::Result<Self, InvalidComponent> {
+        Self::validated(ComponentFilterRule::PostalCode(value.into()))
+    }
 
-    /// Creates a new connection for the Google geocoding API on the specified reactor
-    pub fn new(handle: &tokio_core::reactor::Handle) -> Self {
-        Self {
-            client: Client::new(handle)
-        }
+    /// Creates a validated route rule
+    pub fn route(value: impl Into<String>) -> std::result::Result<Self, InvalidComponent> {
+        Self::validated(ComponentFilterRule::Route(value.into()))
     }
 
-    /// Get the address of the specified coordinates
-    pub fn degeocode(&self, coordinates: impl Into<DegeocodeQuery>) -> impl Future<Item = Vec<Reply>, Error = Error> {
-        self.get(coordinates.into())
+    /// The name of the filtered component, as sent to the API
+    pub fn component(&self) -> &'static str {
+        self.parts().0
     }
 
-    /// Get the coordinates of the specified address
-    pub fn geocode(&self, address: impl Into<GeocodeQuery>) -> impl Future<Item = Vec<Reply>, Error = Error> {
-        self.get(address.into())
+    /// The value the component must match
+    pub fn value(&self) -> &str {
+        self.parts().1
     }
 
-    /// Perform the specified query
-    fn get(&self, i_params: impl ApiQuery) -> impl Future<Item = Vec<Reply>, Error = Error> {
-        // FIXME: unwrap below
-        let mut url_full = Url::parse(Self::URL).unwrap();
-        url_full.set_query(Some(serde_urlencoded::to_string(i_params).unwrap().as_ref()));
-        self.client
-            .get(url_full)
-            .send()
-            .map_err(Error::from)
-            .and_then(move |res| res.into_body().concat2()
-            .map_err(Error::from))
-            .and_then(move |body| serde_json::from_slice(&body)
-            .map_err(Error::from))
-            .and_then(move |reply| {
-                match reply {
-                    ReplyResult { status: StatusCode::Ok, results, .. } => Ok(results),
-                    ReplyResult { status: e, .. }  => Err(e.into()),
-                }
-            })
+    /// Check that this rule is well formed, catching requests the API would reject or fail to match
+    ///
+    /// Values must be non-empty and must not contain the `|` and `:` separators of the components parameter.
+    /// Two letter countries must be ISO 3166-1 codes, and longer countries must look like a name.
+    pub fn validate(&self) -> std::result::Result<(), InvalidComponent> {
+        let (component, value) = self.parts();
+        let invalid = |reason| InvalidComponent { component, value: value.to_string(), reason };
+        if value.trim().is_empty() {
+            return Err(invalid(InvalidComponentReason::Empty));
+        }
+        if value.contains('|') || value.contains(':') {
+            return Err(invalid(InvalidComponentReason::ContainsSeparator));
+        }
+        if let ComponentFilterRule::Country(_) = self {
+            let is_code = value.len() == 2 && value.chars().all(|c| c.is_ascii_alphabetic());
+            let is_name = value.chars().count() > 2
+                && value.chars().next().map_or(false, char::is_alphabetic)
+                && value.chars().all(|c| c.is_alphabetic() || " -.'()".contains(c));
+            if !is_code && !is_name {
+                return Err(invalid(InvalidComponentReason::InvalidCountry));
+            }
+        }
+        Ok(())
     }
-}
 
-/// WGS-84 coordinates that support serializing and deserializing
-#[derive(Clone,Copy,Debug,Shrinkwrap)]
-pub struct Coordinates(WGS84<f64>);
+    fn validated(self) -> std::result::Result<Self, InvalidComponent> {
+        self.validate().map(|_| self)
+    }
 
-impl<'de> serde::Deserialize<'de> for Coordinates {
-    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error> 
-    where D: Deserializer<'de> {
-        #[derive(Deserialize)]
-        pub struct Helper {
-            #[serde(rename="lat")]
-            latitude: f64,
-            #[serde(rename="lng")]
-            longitude: f64,
+    fn parts(&self) -> (&'static str, &str) {
+        match self {
+            ComponentFilterRule::PostalCode(x)=>("postal_code", x),
+            ComponentFilterRule::Country(x)=>("country", x),
+            ComponentFilterRule::Route(x)=>("route", x),
+            ComponentFilterRule::Locality(x)=>("locality", x),
+            ComponentFilterRule::AdministrativeArea(x)=>("administrative_area", x),
         }
-        Helper::deserialize(deserializer)
-            .and_then(|x|WGS84::try_new(x.latitude, x.longitude, 0f64).ok_or_else(||serde::de::Error::custom(format!("Coordinates ({},{}) do not lie on WGS-84 ellipsoid", x.latitude, x.longitude))))
-            .map(Coordinates)
     }
 }
 
-impl Serialize for Coordinates {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
-        serializer.serialize_str(&self.to_string())
-    }
+/// Why a component filter rule is invalid, see `ComponentFilterRule::validate`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InvalidComponentReason {
+    /// The value is empty or only whitespace
+    Empty,
+    /// The value contains `|` or `:`, which separate rules in the components parameter
+    ContainsSeparator,
+    /// The country is neither a two letter ISO 3166-1 code nor a plausible country name
+    InvalidCountry,
 }
 
-impl std::fmt::Display for Coordinates {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{},{}", self.0.latitude_degrees(), self.0.longitude_degrees())
-    }
+/// A component filter rule that failed validation
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct InvalidComponent {
+    /// The name of the filtered component, eg `country`
+    pub component: &'static str,
+    /// The offending value
+    pub value: String,
+    /// Why the value is invalid
+    pub reason: InvalidComponentReason,
 }
 
-impl From<WGS84<f64>> for Coordinates {
-    fn from(v: WGS84<f64>) -> Self {
-        Coordinates(v)
+impl Display for InvalidComponent {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let reason = match self.reason {
+            InvalidComponentReason::Empty => "is empty",
+            InvalidComponentReason::ContainsSeparator => "contains a '|' or ':' separator",
+            InvalidComponentReason::InvalidCountry => "is not a two letter ISO 3166-1 code or a country name",
+        };
+        write!(f, "Invalid {} component \"{}\": {}", self.component, self.value, reason)
     }
 }
 
+impl Fail for InvalidComponent {}
 
-#[derive(Debug, Serialize)]
-/// A query for an address
-pub struct DegeocodeQuery {
-    /// The latitude and longitude values specifying the location for which you wish to obtain the closest, human-readable address.
-    #[serde(rename="latlng")]
-    coordinates: Coordinates,
-
-    /// The language in which to return results.
-    language: Option<Language>,
+impl Serialize for ComponentFilterRule {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
+        let (k, v) = self.parts();
+        serializer.serialize_str(&format!("{}:{}", k, v))
+    }
+}
 
-    /// A filter of one or more address types.
-    ///
-    /// If the parameter contains multiple address types, the API returns all addresses that match any of the types.
-    ///
-    /// A note about processing: The result_type parameter does not restrict the search to the specified address type(s). Rather, the result_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified address type(s).
-    ///
-    /// Note: This parameter is available only for requests that include an API key or a client ID.
-    result_type: Option<ApiSet<Type>>,
+/// A builder for a components filter
+///
+/// For example `ComponentFilter::new().country("US").postal_code("94043")`.
+#[derive(Clone, Debug)]
+pub struct ComponentFilter(ApiSet<ComponentFilterRule>);
 
-    /// A filter of one or more location types.
-    ///
-    /// If the parameter contains multiple location types, the API returns all addresses that match any of the types.
-    ///
-    /// A note about processing: The location_type parameter does not restrict the search to the specified location type(s). Rather, the location_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified location type(s).
-    ///
-    /// Note: This parameter is available only for requests that include an API key or a client ID.
-    location_type: Option<ApiSet<LocationType>>,
+impl Default for ComponentFilter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl DegeocodeQuery {
-    /// Creates a new address query
-    pub fn new(coordinates: impl Into<Coordinates>) -> Self {
-        DegeocodeQuery {
-            coordinates: coordinates.into(),
-            language: None,
-            location_type: None,
-            result_type: None,
-        }
+impl ComponentFilter {
+    /// Creates an empty components filter
+    pub fn new() -> Self {
+        ComponentFilter(ApiSet(HashSet::new()))
     }
 
-    /// The language in which to return results.
-    pub fn language(mut self, i_language: Language) -> Self {
-        self.language = Some(i_language);
-        self
+    /// Matches all the administrative_area levels.
+    pub fn administrative_area(self, i_administrative_area: impl Into<String>) -> Self {
+        self.rule(ComponentFilterRule::AdministrativeArea(i_administrative_area.into()))
     }
 
-    /// A filter of one or more location types.
-    ///
-    /// If the parameter contains multiple location types, the API returns all addresses that match any of the types.
-    ///
-    /// A note about processing: The location_type parameter does not restrict the search to the specified location type(s). Rather, the location_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified location type(s).
-    ///
-    /// Note: This parameter is available only for requests that include an API key or a client ID.
-    pub fn location_type(mut self, i_location_type: ApiSet<LocationType>) -> Self {
-        self.location_type = Some(i_location_type);
-        self
+    /// Matches a country name or a two letter ISO 3166-1 country code.
+    pub fn country(self, i_country: impl Into<String>) -> Self {
+        self.rule(ComponentFilterRule::Country(i_country.into()))
     }
 
-    /// A filter of one or more address types.
-    ///
-    /// If the parameter contains multiple address types, the API returns all addresses that match any of the types.
-    ///
-    /// A note about processing: The result_type parameter does not restrict the search to the specified address type(s). Rather, the result_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified address type(s).
-    ///
-    /// Note: This parameter is available only for requests that include an API key or a client ID.
-    pub fn result_type(mut self, i_result_type: ApiSet<Type>) -> Self {
-        self.result_type = Some(i_result_type);
-        self
+    /// Matches matches against locality and sublocality types.
+    pub fn locality(self, i_locality: impl Into<String>) -> Self {
+        self.rule(ComponentFilterRule::Locality(i_locality.into()))
     }
-}
 
-impl<T> From<T> for DegeocodeQuery where Coordinates: From<T> {
-    fn from(v: T) -> Self {
-        Self::new(v)
+    /// Matches postal_code and postal_code_prefix.
+    pub fn postal_code(self, i_postal_code: impl Into<String>) -> Self {
+        self.rule(ComponentFilterRule::PostalCode(i_postal_code.into()))
     }
-}
 
-impl ApiQuery for DegeocodeQuery{}
+    /// Matches the long or short name of a route.
+    pub fn route(self, i_route: impl Into<String>) -> Self {
+        self.rule(ComponentFilterRule::Route(i_route.into()))
+    }
 
-/// A query for coordinates
-#[derive(Debug, Serialize)]
-pub struct GeocodeQuery {
-    #[serde(flatten)]
-    filter: Option<Place>,
+    /// Adds an arbitrary rule
+    pub fn rule(mut self, i_rule: ComponentFilterRule) -> Self {
+        (self.0).0.insert(i_rule);
+        self
+    }
 
-    /// The bounding box of the viewport within which to bias geocode results more prominently.
-    /// This parameter will only influence, not fully restrict, results from the geocoder.
-    /// (For more information see Viewport Biasing below.)
-    bounds: Option<Viewport>,
+    /// The rules of this filter
+    pub fn build(self) -> ApiSet<ComponentFilterRule> {
+        self.0
+    }
 
-    /// The language in which to return results.
-    language: Option<Language>,
+    /// The rules of this filter, if they are all valid, see `ComponentFilterRule::validate`
+    pub fn try_build(self) -> Result<ApiSet<ComponentFilterRule>> {
+        for rule in self.0.iter() {
+            rule.validate()?;
+        }
+        Ok(self.0)
+    }
+}
 
-    /// The region code.
-    ///
-    /// This parameter will only influence, not fully restrict, results from the geocoder.
-    /// (For more information see Region Biasing below.)
-    region: Option<Region>,
+impl From<ComponentFilter> for ApiSet<ComponentFilterRule> {
+    fn from(v: ComponentFilter) -> Self {
+        v.build()
+    }
 }
 
-impl GeocodeQuery {
-    /// Creates a new coordinates query
-    pub fn new(filter: impl Into<Place>) -> Self {
-        GeocodeQuery {
-            filter: Some(filter.into()),
-            //components: None,
-            bounds: None,
-            language: None,
-            region: None,
+impl From<ComponentFilter> for Place {
+    fn from(v: ComponentFilter) -> Self {
+        Place::ComponentFilter {
+            components: v.build(),
         }
     }
+}
 
-    /// The bounding box of the viewport within which to bias geocode results more prominently.
-    /// This parameter will only influence, not fully restrict, results from the geocoder.
-    /// (For more information see Viewport Biasing below.)
-    pub fn bounds(mut self, i_bounds: Viewport) -> Self {
-        self.bounds = Some(i_bounds);
-        self
+/// A query that can be sent to the Google geocoding API
+pub trait ApiQuery : Debug + Serialize {
+    /// Check the query locally for mistakes the API would reject with a generic `InvalidRequest`
+    fn validate(&self) -> std::result::Result<(), InvalidQuery> {
+        Ok(())
     }
+}
 
-    /// The language in which to return results.
-    pub fn language(mut self, i_language: Language) -> Self {
-        self.language = Some(i_language);
-        self
+/// Build the URL requesting the specified query from the endpoint at `base`, optionally authenticated with an API key
+///
+/// This does no I/O, so it can be used to test how queries are serialized without a network or reactor.
+/// Any query already on `base` is replaced. Fails if the query is invalid, see `ApiQuery::validate`.
+pub fn build_request_url(base: &Url, query: &impl ApiQuery, key: Option<&str>) -> Result<Url> {
+    query.validate()?;
+    let mut url = base.clone();
+    url.set_query(Some(serde_urlencoded::to_string(query)?.as_ref()));
+    if let Some(key) = key {
+        url.query_pairs_mut().append_pair("key", key);
     }
+    Ok(url)
+}
 
-    /// The region code.
-    ///
-    /// This parameter will only influence, not fully restrict, results from the geocoder.
-    /// (For more information see Region Biasing below.)
-    pub fn region(mut self, i_region: Region) -> Self {
-        self.region = Some(i_region);
-        self
+/// A query that is rejected before being sent, see `ApiQuery::validate`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InvalidQuery {
+    /// The query has neither an address nor component filters
+    MissingAddressAndComponents,
+    /// The address is empty or only whitespace
+    EmptyAddress,
+    /// The components filter has no rules
+    NoComponentFilters,
+    /// A component filter rule is invalid
+    InvalidComponent(InvalidComponent),
+}
+
+impl Display for InvalidQuery {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            InvalidQuery::MissingAddressAndComponents => f.write_str("The query needs an address or component filters"),
+            InvalidQuery::EmptyAddress => f.write_str("The address is empty"),
+            InvalidQuery::NoComponentFilters => f.write_str("No component filters provided"),
+            InvalidQuery::InvalidComponent(e) => Display::fmt(e, f),
+        }
     }
 }
 
-impl ApiQuery for GeocodeQuery{}
+impl Fail for InvalidQuery {
+    fn cause(&self) -> Option<&dyn Fail> {
+        match self {
+            InvalidQuery::InvalidComponent(e) => Some(e),
+            _ => None,
+        }
+    }
+}
 
-impl<T> From<T> for GeocodeQuery where Place: From<T> {
-    fn from(v: T) -> Self {
-        Self::new(v)
+impl From<InvalidComponent> for InvalidQuery {
+    fn from(v: InvalidComponent) -> Self {
+        InvalidQuery::InvalidComponent(v)
     }
 }
 
-/// An address in one of various formats
-#[derive(Debug, Serialize)]
-#[serde(untagged)]
-pub enum Place {
-    /// A specific place
-    Address {
-        /// The street address that you want to geocode,
-        /// in the format used by the national postal service of the country concerned.
-        /// Additional address elements such as business names and unit, suite or floor numbers should be avoided.
-        /// Please refer to the FAQ for additional guidance. 
-        address: String
-    },
-    /// The address broken down into components
-    ComponentFilter {
-        /// The components filter is required if the request doesn't include an address.
-        /// Each element in the components filter consists of a component:value pair,
-        /// and fully restricts the results from the geocoder.
-        /// See more information about component filtering below.
-        components: ApiSet<ComponentFilterRule>
-    },
+/// The HTTP status and headers of a response from the API
+///
+/// Quota and usage hints are carried in the headers, so inspecting them lets
+/// applications throttle themselves before hitting `OverQueryLimit`.
+#[derive(Clone, Debug)]
+pub struct ResponseInfo {
+    /// The HTTP status of the response
+    pub status: reqwest::StatusCode,
+    /// The headers of the response
+    pub headers: Headers,
 }
 
-impl<T> From<T> for Place where T: Into<String> {
-    fn from(s: T) -> Self {
-        Place::Address { 
-            address: s.into()
-        }
-    }
+/// Replies along with how long the request took, see `Connection::geocode_timed`
+#[derive(Clone, Debug)]
+pub struct TimedReply {
+    /// The replies
+    pub replies: Vec<Reply>,
+    /// The wall-clock time from sending the request to decoding the reply
+    pub elapsed: Duration,
 }
 
-/// A unique identifier that can be used with other Google APIs.
-/// For example, you can use the place_id in a Places SDK request to get details of a local business, such as phone number, opening hours, user reviews, and more. See the place ID overview.
-#[derive(Debug,Deserialize,Eq,Hash,PartialEq,Serialize)]
-pub struct PlaceId(String);
+/// Notified by a `Connection` of each request it sends and its outcome
+///
+/// This lets metrics, eg for Prometheus or statsd, be recorded without this crate depending on a metrics library.
+/// Each retry is a separate request. All methods do nothing by default.
+pub trait Observer {
+    /// A request is about to be sent to the specified URL
+    fn on_request(&self, _url: &Url) {}
 
-/// Get all the coordinates associated with the specified filter
-pub fn geocode(address: impl Into<GeocodeQuery>) -> Result<impl Iterator<Item=Coordinates>> {
-    let mut core = Core::new()?;
-    let core_handle = core.handle();
-    Ok(core.run(Connection::new(&core_handle).geocode(address))?.into_iter().map(|x|x.geometry.location))
+    /// A reply was received and decoded after `elapsed`, whatever its status
+    fn on_response(&self, _status: &StatusCode, _elapsed: Duration) {}
+
+    /// No reply could be received or decoded, eg because of a network error
+    fn on_error(&self, _error: &Error) {}
 }
 
-/// Get all the addresses associated with the specified coordinates
-pub fn degeocode(coordinates: impl Into<DegeocodeQuery>) -> Result<impl Iterator<Item=FormattedAddress>> {
-    let mut core = Core::new()?;
-    let core_handle = core.handle();
-    Ok(core.run(Connection::new(&core_handle).degeocode(coordinates))?.into_iter().map(|x|x.formatted_address))
+/// Represents a connection to the Google geocoding API
+///
+/// Clones share the underlying HTTP client and the last response.
+///
+/// Requests are sent over HTTP/1.1, reusing pooled keep-alive connections to the API.
+/// The underlying client does not support HTTP/2, so requests cannot be multiplexed over a single connection;
+/// to geocode at scale, send requests concurrently instead, eg with `geocode_batch_with_concurrency`.
+#[derive(Clone)]
+pub struct Connection {
+    base_url: Url,
+    channel: Option<String>,
+    client: Client,
+    handle: tokio_core::reactor::Handle,
+    last_response: Rc<RefCell<Option<ResponseInfo>>>,
+    max_retries: u32,
+    observer: Option<Rc<dyn Observer>>,
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
+impl Connection {
+    /// The root of the geocoding API, to which the output format is appended
+    pub const API_ROOT: &'static str = "https://maps.google.com/maps/api/geocode/";
+    /// The path selecting JSON output
+    ///
+    /// The API also offers `xml`, but replies are always decoded as JSON, so this is the only supported format.
+    pub const JSON_FORMAT: &'static str = "json";
+    const HEALTH_CHECK_ADDRESS: &'static str = "1600 Amphitheatre Parkway, Mountain View, CA";
+    const RETRY_BASE_DELAY_MS: u64 = 100;
 
-    const ADDRESS: &str = "1600 Amphitheatre Pkwy, Mountain View, CA 94043, USA";
-    const COORDINATES: (f64, f64) = (37.42241, -122.08561);
+    /// Creates a new connection for the Google geocoding API on the specified reactor
+    pub fn new(handle: &tokio_core::reactor::Handle) -> Self {
+        let url = Url::parse(Self::API_ROOT)
+            .and_then(|root| root.join(Self::JSON_FORMAT))
+            .expect("API_ROOT and JSON_FORMAT form a valid URL");
+        Self::with_insecure_base_url(handle, url)
+    }
 
-    fn test_print<L>(i_label: L, i_rr: impl Future<Item = Vec<Reply>, Error=Error>) -> impl Future<Item=(),Error=()> 
-        where L: Debug + 'static {
-        let label = i_label;
-        i_rr
-            .map(move |rr| println!("{:#?}: {:#?}", label, rr.iter().map(|r|&r.geometry.location).collect::<Vec<_>>()))
-            .map_err(|e| error!("{:?}", e))
+    /// Creates a new connection on the specified reactor that sends requests to the specified endpoint
+    ///
+    /// This is mostly useful for pointing at a proxy.
+    /// Google requires HTTPS, and API keys must not travel in cleartext,
+    /// so this fails unless the endpoint is an `https` URL.
+    ///
+    /// It also fails if the endpoint cannot take query parameters, or already has some,
+    /// as they would be replaced by those of each query.
+    pub fn with_base_url(handle: &tokio_core::reactor::Handle, base_url: Url) -> Result<Self> {
+        if base_url.scheme() != "https" {
+            bail!("Refusing to send requests to insecure URL {}", base_url);
+        }
+        if base_url.cannot_be_a_base() || base_url.query().is_some() {
+            bail!("Base URL {} must be a hierarchical URL without a query", base_url);
+        }
+        Ok(Self::with_insecure_base_url(handle, base_url))
+    }
 
+    /// Creates a new connection on the specified reactor that sends requests to the endpoint at the specified URL
+    ///
+    /// Like `with_base_url`, but parses the URL first, failing with a clear error if it is malformed.
+    /// The URL is only parsed here, never again per request.
+    pub fn with_base_url_str(handle: &tokio_core::reactor::Handle, base_url: &str) -> Result<Self> {
+        let url = Url::parse(base_url).map_err(|e| format_err!("Invalid base URL \"{}\": {}", base_url, e))?;
+        Self::with_base_url(handle, url)
     }
 
-    fn test_start() -> (Core, Connection) {
-        let core = Core::new().expect("Failed to initialize core");
-        let connection = Connection::new(&core.handle());
+    /// Creates a new connection on the specified reactor that sends requests to the specified endpoint,
+    /// allowing insecure schemes such as plain `http`
+    ///
+    /// This is intended for local mock servers. See `with_base_url`.
+    pub fn with_insecure_base_url(handle: &tokio_core::reactor::Handle, base_url: Url) -> Self {
+        Self {
+            base_url,
+            channel: None,
+            client: Client::new(handle),
+            handle: handle.clone(),
+            last_response: Rc::new(RefCell::new(None)),
+            max_retries: 0,
+            observer: None,
+        }
+    }
 
-        (core, connection)
+    /// The channel to attribute requests to in usage reports
+    ///
+    /// This is appended to every request, letting organizations segment usage across applications sharing an account.
+    pub fn channel(mut self, i_channel: impl Into<String>) -> Self {
+        self.channel = Some(i_channel.into());
+        self
     }
 
-    fn test_stop<T>(mut i_core: Core, i_future: T)
-        where T: futures::Future<Item=(), Error=()> {
-        i_core.run(i_future).expect("Failed running tests")
+    /// An observer to notify of each request sent and its outcome, eg to record metrics
+    pub fn observer(mut self, i_observer: impl Observer + 'static) -> Self {
+        self.observer = Some(Rc::new(i_observer));
+        self
     }
 
-    #[test]
-    fn address() {
-        super::degeocode(WGS84::try_new(COORDINATES.0, COORDINATES.1, 0.0).unwrap()).unwrap();
+    /// The HTTP status and headers of the most recent response, if any has been received
+    ///
+    /// Retried requests overwrite this, so it describes the final attempt.
+    pub fn last_response(&self) -> Option<ResponseInfo> {
+        self.last_response.borrow().clone()
     }
 
-    #[test]
-    fn coordinates() {
-        super::geocode(ADDRESS).unwrap();
+    /// The number of times to retry a request that failed with a retryable status
+    ///
+    /// See `StatusCode::is_retryable`.
+    /// If the reply carries a `Retry-After` header, the retry is delayed accordingly;
+    /// otherwise the delay grows exponentially with each attempt.
+    /// Random jitter is added to the delay so that concurrent requests don't all retry at once.
+    pub fn retries(mut self, i_max_retries: u32) -> Self {
+        self.max_retries = i_max_retries;
+        self
     }
 
-    #[test]
-    fn connection_both() {
-        let (core, connection) = test_start();
-        let tests = futures::Future::join(
-            test_print("Basic", connection.geocode(ADDRESS)),
-            test_print("Basic", connection.degeocode(
-                WGS84::new(COORDINATES.0, COORDINATES.1, 0f64)
-            )),
-        ).map(|_|());
-        test_stop(core, tests)
+    /// Get the address of the specified coordinates
+    ///
+    /// Some places, such as the middle of the ocean, legitimately have no address,
+    /// so unlike `geocode`, a `ZeroResults` reply gives no replies rather than an error.
+    /// Use `request_raw` to tell it apart from other empty replies.
+    ///
+    /// The query's `result_type` and `location_type` filters are also applied to the replies,
+    /// as the API ignores them for requests without an API key.
+    pub fn degeocode(&self, coordinates: impl Into<DegeocodeQuery>) -> impl Future<Item = Vec<Reply>, Error = Error> {
+        let query = coordinates.into();
+        let filters = query.clone();
+        self.get(query)
+            .or_else(Self::zero_results_as_empty)
+            .map(move |replies| filters.retain_matching(replies))
     }
 
-    #[test]
-    fn compare() {
-        let mut coordinates = super::geocode(ADDRESS).unwrap();
-        let first_coordinates = coordinates.next().unwrap();
-        let mut addresses = super::degeocode(first_coordinates).unwrap();
-        let first_address = addresses.next().unwrap();
-        assert_eq!(ADDRESS, first_address.0);
+    /// Recover from a `ZeroResults` error with no replies
+    fn zero_results_as_empty(e: Error) -> Result<Vec<Reply>> {
+        match e.downcast_ref::<ApiError>() {
+            Some(ApiError { status: StatusCode::ZeroResults, .. }) => Ok(Vec::new()),
+            _ => Err(e),
+        }
+    }
+
+    /// Get the coordinates of the specified address
+    ///
+    /// This accepts anything that converts into a `GeocodeQuery`, including a plain address
+    /// and a `GeocodeQuery` configured with a per-request language or region:
+    ///
+    /// ```no_run
+    /// extern crate google_geocoding;
+    /// extern crate tokio_core;
+    ///
+    /// # #[cfg(feature = "full-enums")] {
+    /// use google_geocoding::{Connection, GeocodeQuery, Language, Region};
+    /// use tokio_core::reactor::Core;
+    ///
+    /// let mut core = Core::new().unwrap();
+    /// let connection = Connection::new(&core.handle());
+    /// let query = GeocodeQuery::new("Rue de Rivoli, Paris")
+    ///     .language(Language::French)
+    ///     .region(Region::France);
+    /// let reply = core.run(connection.geocode(query)).unwrap();
+    /// # }
+    /// ```
+    ///
+    /// If the query was built with `GeocodeQuery::language_preference`, it is retried in each
+    /// of the remaining languages in turn for as long as the API finds no results.
+    pub fn geocode(&self, address: impl Into<GeocodeQuery>) -> impl Future<Item = Vec<Reply>, Error = Error> {
+        let connection = self.clone();
+        Self::retry_languages(address.into(), move |query| connection.get(query))
+    }
+
+    /// Request the specified query with `get`, then again in each of its retry languages until there are results
+    ///
+    /// Each retry is only built, and sent, once the previous request found no results.
+    fn retry_languages<T, F, G>(mut query: GeocodeQuery, get: G) -> Box<dyn Future<Item = T, Error = Error>>
+        where T: 'static, F: Future<Item = T, Error = Error> + 'static, G: Fn(GeocodeQuery) -> F + 'static {
+        let retries = std::mem::take(&mut query.retry_languages);
+        let first = get(query.clone());
+        if retries.is_empty() {
+            return Box::new(first);
+        }
+        Box::new(first.or_else(move |e| {
+            match e.downcast_ref::<ApiError>() {
+                Some(ApiError { status: StatusCode::ZeroResults, .. }) => future::Either::A(Self::retry_languages(query.language_preference(&retries), get)),
+                _ => future::Either::B(future::err(e)),
+            }
+        }))
+    }
+
+    /// Geocode the specified address, then reverse geocode the first result
+    ///
+    /// This canonicalizes an address via Google, returning the coordinates it resolved to
+    /// along with the most relevant address at those coordinates.
+    pub fn roundtrip(&self, address: impl Into<GeocodeQuery>) -> impl Future<Item = (Coordinates, FormattedAddress), Error = Error> {
+        let connection = self.clone();
+        self.geocode(address).and_then(move |replies| {
+            let coordinates = match replies.into_iter().next() {
+                Some(reply) => reply.geometry.location,
+                None => return future::Either::B(future::err(format_err!("No results to reverse geocode"))),
+            };
+            future::Either::A(connection.degeocode(coordinates).and_then(move |replies| {
+                replies.into_iter().next()
+                    .map(|reply| (coordinates, reply.formatted_address))
+                    .ok_or_else(|| format_err!("No address found at {}", coordinates))
+            }))
+        })
+    }
+
+    /// Get the coordinates of the specified address within a known place, eg a unit within a building
+    ///
+    /// The API does not take a place as context, so this first looks up the place by its ID,
+    /// then geocodes the address biased towards the place's viewport.
+    /// As with any bounds, this only biases the results, which may still lie outside the place.
+    pub fn geocode_within_place(&self, place_id: impl Into<PlaceId>, address: impl Into<Place>) -> impl Future<Item = Vec<Reply>, Error = Error> {
+        let connection = self.clone();
+        let address = address.into();
+        self.get(PlaceIdQuery { place_id: place_id.into() }).and_then(move |places| {
+            match places.first() {
+                Some(place) => future::Either::A(connection.geocode(GeocodeQuery::new(address).bounds(place.geometry.viewport))),
+                None => future::Either::B(future::err(format_err!("No place found for the place ID"))),
+            }
+        })
+    }
+
+    /// Get the coordinates of the specified address, keeping the status of the reply
+    ///
+    /// Unlike `geocode`, finding no results is not an error, but a reply whose status is `ZeroResults`.
+    /// Other unsuccessful statuses are still retried and reported as an `ApiError`.
+    pub fn geocode_replies(&self, address: impl Into<GeocodeQuery>) -> impl Future<Item = Replies, Error = Error> {
+        let connection = self.clone();
+        Self::retry_languages(address.into(), move |query| connection.get_with(query, ReplyResult::into_replies))
+            .or_else(|e| match e.downcast::<ApiError>() {
+                Ok(ApiError { status: StatusCode::ZeroResults, error_message }) => Ok(Replies { status: StatusCode::ZeroResults, message: error_message, results: Vec::new() }),
+                Ok(e) => Err(e.into()),
+                Err(e) => Err(e),
+            })
+    }
+
+    /// Get the coordinates of the specified address, along with how long the request took
+    ///
+    /// The time is measured from when the future is first polled until the reply is decoded,
+    /// including any retries.
+    pub fn geocode_timed(&self, address: impl Into<GeocodeQuery>) -> impl Future<Item = TimedReply, Error = Error> {
+        let replies = self.geocode(address);
+        future::lazy(move || {
+            let start = Instant::now();
+            replies.map(move |replies| TimedReply {
+                replies,
+                elapsed: start.elapsed(),
+            })
+        })
+    }
+
+    /// Get the coordinates of each of the specified addresses, with at most `limit` requests in flight at once
+    ///
+    /// Capping the parallelism helps to stay under the API's rate limit.
+    /// The results are in the same order as the queries, and each query succeeds or fails on its own.
+    ///
+    /// ```no_run
+    /// extern crate google_geocoding;
+    /// extern crate tokio_core;
+    ///
+    /// use google_geocoding::Connection;
+    /// use tokio_core::reactor::Core;
+    ///
+    /// let mut core = Core::new().unwrap();
+    /// let connection = Connection::new(&core.handle()).retries(3);
+    /// let addresses = vec!["Paris", "London", "Berlin"];
+    /// let batch = connection.geocode_batch_with_concurrency(addresses, 2);
+    /// for result in core.run(batch).unwrap() {
+    ///     match result {
+    ///         Ok(replies) => println!("{} candidates", replies.len()),
+    ///         Err(e) => println!("Failed: {}", e),
+    ///     }
+    /// }
+    /// ```
+    pub fn geocode_batch_with_concurrency<Q>(&self, queries: impl IntoIterator<Item = Q>, limit: usize) -> impl Future<Item = Vec<Result<Vec<Reply>>>, Error = Error>
+        where Q: Into<GeocodeQuery> {
+        let requests = queries.into_iter()
+            .map(|query| self.geocode(query).then(Ok::<_, Error>))
+            .collect::<Vec<_>>();
+        Self::buffered(requests, limit)
+    }
+
+    /// Get the coordinates of each of the specified addresses, keeping each result with the key of its address
+    ///
+    /// The key can be anything identifying an input, such as a row ID, so results can be written back
+    /// without relying on their order. Each query succeeds or fails on its own.
+    /// As with `geocode_batch_with_concurrency`, at most `limit` requests are in flight at once.
+    pub fn geocode_labeled<K, Q>(&self, items: impl IntoIterator<Item = (K, Q)>, limit: usize) -> impl Future<Item = Vec<(K, Result<Vec<Reply>>)>, Error = Error>
+        where Q: Into<GeocodeQuery> {
+        let requests = items.into_iter()
+            .map(|(key, query)| self.geocode(query).then(move |result| Ok::<_, Error>((key, result))))
+            .collect::<Vec<_>>();
+        Self::buffered(requests, limit)
+    }
+
+    /// Run the specified requests in order, with at most `limit` of them in flight at once
+    fn buffered<F: Future>(requests: Vec<F>, limit: usize) -> impl Future<Item = Vec<F::Item>, Error = F::Error> {
+        stream::iter_ok(requests).buffered(limit.max(1)).collect()
+    }
+
+    /// Get the coordinates of the specified address in each of the specified languages
+    ///
+    /// The API returns a single language per request, so one request is sent per distinct language,
+    /// with at most `limit` in flight at once. Fails if any of the requests fails.
+    /// Each language is requested without retries, so results are only listed under the language they were requested in.
+    pub fn geocode_multilang<L>(&self, address: impl Into<GeocodeQuery>, languages: &[L], limit: usize) -> impl Future<Item = HashMap<LanguageCode, Vec<Reply>>, Error = Error>
+        where L: Clone + Into<LanguageCode> {
+        let query = address.into();
+        let mut seen = HashSet::new();
+        let languages = languages.iter().cloned().map(L::into).filter(|language: &LanguageCode| seen.insert(language.clone())).collect::<Vec<_>>();
+        let requests = languages.iter().cloned()
+            .map(|language| self.geocode(query.clone().language_preference(&[language])))
+            .collect::<Vec<_>>();
+        Self::buffered(requests, limit).map(move |replies| languages.into_iter().zip(replies).collect())
+    }
+
+    /// Get the coordinates of the specified address as a stream of individual candidates
+    ///
+    /// The whole reply is received and decoded before the first candidate is yielded,
+    /// but consumers can stop early, eg with `take_while`, once they find an acceptable one.
+    pub fn geocode_each(&self, address: impl Into<GeocodeQuery>) -> impl Stream<Item = Reply, Error = Error> {
+        self.geocode(address).map(stream::iter_ok).flatten_stream()
+    }
+
+    /// Check that the API is reachable and accepts requests from this connection
+    ///
+    /// This geocodes a known address once and succeeds if the status is `Ok` or `ZeroResults`.
+    /// Otherwise it fails with an `ApiError`, whose status is `RequestDenied` if the key is misconfigured.
+    pub fn health_check(&self) -> impl Future<Item = (), Error = Error> {
+        self.request_raw(GeocodeQuery::new(Self::HEALTH_CHECK_ADDRESS)).and_then(|reply| {
+            match reply {
+                ReplyResult { status: StatusCode::Ok, .. } | ReplyResult { status: StatusCode::ZeroResults, .. } => Ok(()),
+                ReplyResult { status, error_message, .. } => Err(ApiError { status, error_message }.into()),
+            }
+        })
+    }
+
+    /// Send the specified query once and return the results along with the exact bytes of the reply body
+    ///
+    /// This is useful for audit logging, or to reproduce parsing issues.
+    /// Like `request_raw`, this does not retry.
+    pub fn geocode_raw_bytes(&self, address: impl Into<GeocodeQuery>) -> impl Future<Item = (Vec<Reply>, Vec<u8>), Error = Error> {
+        let client = self.client.clone();
+        let last_response = self.last_response.clone();
+        let observer = self.observer.clone();
+        future::result(self.dry_run(address.into()))
+            .and_then(move |url_full| Self::fetch_body(&client, url_full, &last_response, &observer))
+            .and_then(|(_, result, body)| {
+                let replies = result?.into_results()?;
+                Ok((replies, body.to_vec()))
+            })
+    }
+
+    /// Send the specified query once and return the decoded reply, even if its status is unsuccessful
+    ///
+    /// Unlike `geocode` and `degeocode`, this neither retries nor turns the status into an error,
+    /// leaving error handling and logging to the caller.
+    /// An HTTP 429 response is reported as an `OverQueryLimit` reply.
+    pub fn request_raw(&self, query: impl ApiQuery) -> impl Future<Item = ReplyResult, Error = Error> {
+        let client = self.client.clone();
+        let last_response = self.last_response.clone();
+        let observer = self.observer.clone();
+        future::result(self.dry_run(query))
+            .and_then(move |url_full| Self::fetch(&client, url_full, &last_response, &observer))
+            .and_then(|(_, result)| result)
+    }
+
+    /// Build the URL that would be requested for the specified query, without sending it
+    ///
+    /// This is useful for auditing exactly what will be sent (and billed) for a query.
+    /// Fails without sending anything if the query is invalid, see `ApiQuery::validate`.
+    pub fn dry_run(&self, query: impl ApiQuery) -> Result<Url> {
+        let mut url_full = build_request_url(&self.base_url, &query, None)?;
+        if let Some(ref channel) = self.channel {
+            url_full.query_pairs_mut().append_pair("channel", channel);
+        }
+        Ok(url_full)
+    }
+
+    /// Perform the specified query
+    fn get(&self, i_params: impl ApiQuery) -> impl Future<Item = Vec<Reply>, Error = Error> {
+        self.get_with(i_params, ReplyResult::into_results)
+    }
+
+    /// Perform the specified query, turning each decoded reply into a result with `decode`
+    ///
+    /// Errors returned by `decode` are retried like any other if they are retryable.
+    fn get_with<T>(&self, i_params: impl ApiQuery, decode: fn(ReplyResult) -> Result<T>) -> impl Future<Item = T, Error = Error> {
+        let client = self.client.clone();
+        let handle = self.handle.clone();
+        let last_response = self.last_response.clone();
+        let max_retries = self.max_retries;
+        let observer = self.observer.clone();
+        future::result(self.dry_run(i_params)).and_then(move |url_full| future::loop_fn(0, move |attempt| {
+            let handle = handle.clone();
+            Self::send(&client, url_full.clone(), &last_response, &observer, decode)
+                .and_then(move |(retry_after, result)| {
+                    match result {
+                        Err(ref e) if attempt < max_retries && Self::is_retryable(e) => {
+                            let delay = Self::retry_delay(attempt, retry_after);
+                            future::Either::A(future::result(Timeout::new(delay, &handle))
+                                .flatten()
+                                .map_err(Error::from)
+                                .map(move |_| Loop::Continue(attempt + 1)))
+                        },
+                        result => future::Either::B(future::result(result.map(Loop::Break))),
+                    }
+                })
+        }))
+    }
+
+    /// Send a single request, returning the results along with any requested retry delay
+    fn send<T>(client: &Client, url: Url, last_response: &Rc<RefCell<Option<ResponseInfo>>>, observer: &Option<Rc<dyn Observer>>, decode: fn(ReplyResult) -> Result<T>) -> impl Future<Item = (Option<Duration>, Result<T>), Error = Error> {
+        Self::fetch(client, url, last_response, observer)
+            .map(move |(retry_after, result)| (retry_after, result.and_then(decode)))
+    }
+
+    /// Send a single request, returning the decoded envelope along with any requested retry delay
+    ///
+    /// The status and headers of the response are recorded in `last_response`.
+    fn fetch(client: &Client, url: Url, last_response: &Rc<RefCell<Option<ResponseInfo>>>, observer: &Option<Rc<dyn Observer>>) -> impl Future<Item = (Option<Duration>, Result<ReplyResult>), Error = Error> {
+        Self::fetch_body(client, url, last_response, observer)
+            .map(|(retry_after, result, _)| (retry_after, result))
+    }
+
+    /// Send a single request, returning the decoded envelope and the raw body along with any requested retry delay
+    ///
+    /// The status and headers of the response are recorded in `last_response`,
+    /// and the observer, if any, is notified of the request and its outcome.
+    fn fetch_body(client: &Client, url: Url, last_response: &Rc<RefCell<Option<ResponseInfo>>>, observer: &Option<Rc<dyn Observer>>) -> impl Future<Item = (Option<Duration>, Result<ReplyResult>, Chunk), Error = Error> {
+        let last_response = last_response.clone();
+        let observer = observer.clone();
+        let error_observer = observer.clone();
+        if let Some(ref observer) = observer {
+            observer.on_request(&url);
+        }
+        let start = Instant::now();
+        client
+            .get(url)
+            .send()
+            .map_err(Error::from)
+            .and_then(move |res| {
+                *last_response.borrow_mut() = Some(ResponseInfo {
+                    status: res.status(),
+                    headers: res.headers().clone(),
+                });
+                let retry_after = Self::retry_after(res.headers());
+                let too_many_requests = res.status() == reqwest::StatusCode::TooManyRequests;
+                res.into_body().concat2()
+                    .map_err(Error::from)
+                    .map(move |body| (retry_after, too_many_requests, body))
+            })
+            .map(move |(retry_after, too_many_requests, body)| {
+                let result = Self::parse(too_many_requests, &body);
+                if let Some(ref observer) = observer {
+                    match result {
+                        Ok(ref reply) => observer.on_response(&reply.status, start.elapsed()),
+                        Err(ref e) => observer.on_error(e),
+                    }
+                }
+                (retry_after, result, body)
+            })
+            .map_err(move |e| {
+                if let Some(ref observer) = error_observer {
+                    observer.on_error(&e);
+                }
+                e
+            })
+    }
+
+    /// Decode a reply body, reporting an HTTP 429 response as an `OverQueryLimit` reply
+    fn parse(too_many_requests: bool, body: &[u8]) -> Result<ReplyResult> {
+        if too_many_requests {
+            Ok(ReplyResult {
+                error_message: Some("HTTP 429 Too Many Requests".to_string()),
+                results: Vec::new(),
+                status: StatusCode::OverQueryLimit,
+            })
+        } else {
+            serde_json::from_slice(Self::strip_bom(body)).map_err(Error::from)
+        }
+    }
+
+    /// Strip the UTF-8 byte order mark some proxies prepend to bodies
+    ///
+    /// Trailing whitespace needs no such treatment, as the decoder already tolerates it.
+    fn strip_bom(body: &[u8]) -> &[u8] {
+        if body.starts_with(b"\xEF\xBB\xBF") {
+            &body[3..]
+        } else {
+            body
+        }
+    }
+
+    /// Whether a failed request may succeed if it is sent again
+    fn is_retryable(e: &Error) -> bool {
+        e.downcast_ref::<ApiError>().map_or(false, ApiError::is_retryable)
+    }
+
+    /// The delay requested by a `Retry-After` header, in either its seconds or HTTP-date form
+    fn retry_after(headers: &Headers) -> Option<Duration> {
+        match headers.get::<RetryAfter>() {
+            Some(RetryAfter::Delay(delay)) => Some(*delay),
+            Some(RetryAfter::DateTime(date)) => Some(SystemTime::from(*date)
+                .duration_since(SystemTime::now())
+                .unwrap_or_else(|_| Duration::from_secs(0))),
+            None => None,
+        }
+    }
+
+    /// How long to wait before the next attempt, with up to 50% random jitter added
+    fn retry_delay(attempt: u32, retry_after: Option<Duration>) -> Duration {
+        use rand::Rng;
+        let base = retry_after
+            .unwrap_or_else(|| Duration::from_millis(Self::RETRY_BASE_DELAY_MS << attempt.min(16)));
+        let base_ms = base.as_secs() * 1000 + u64::from(base.subsec_millis());
+        base + Duration::from_millis(rand::thread_rng().gen_range(0, base_ms / 2 + 1))
+    }
+}
+
+/// WGS-84 coordinates that support serializing and deserializing
+#[derive(Clone,Copy,Debug,Shrinkwrap)]
+pub struct Coordinates(WGS84<f64>);
+
+impl<'de> serde::Deserialize<'de> for Coordinates {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error> 
+    where D: Deserializer<'de> {
+        #[derive(Deserialize)]
+        pub struct Helper {
+            #[serde(rename="lat", deserialize_with="serde_util::f64_or_string")]
+            latitude: f64,
+            #[serde(rename="lng", deserialize_with="serde_util::f64_or_string")]
+            longitude: f64,
+        }
+        Helper::deserialize(deserializer)
+            .and_then(|x| Coordinates::validated(x.latitude, x.longitude).map_err(serde::de::Error::custom))
+    }
+}
+
+impl Coordinates {
+    /// Creates coordinates from degrees, describing why they are invalid if necessary
+    ///
+    /// `WGS84::try_new` only rejects latitudes outside ±90 and longitudes outside ±180, so the poles and antimeridian are valid.
+    fn validated(latitude: f64, longitude: f64) -> std::result::Result<Self, String> {
+        if !latitude.is_finite() || !longitude.is_finite() {
+            return Err(format!("Coordinates ({},{}) are not finite", latitude, longitude));
+        }
+        WGS84::try_new(latitude, longitude, 0f64)
+            .map(Coordinates)
+            .ok_or_else(|| format!("Coordinates ({},{}) do not lie on WGS-84 ellipsoid", latitude, longitude))
+    }
+
+    /// Creates coordinates from any finite degrees, clamping the latitude and wrapping the longitude into range
+    ///
+    /// For example, a longitude of 190 becomes -170.
+    pub fn lenient(latitude: f64, longitude: f64) -> Result<Self> {
+        if !latitude.is_finite() || !longitude.is_finite() {
+            bail!("Coordinates ({},{}) are not finite", latitude, longitude);
+        }
+        let longitude = if longitude.abs() <= 180.0 {
+            longitude
+        } else {
+            (longitude + 180.0).rem_euclid(360.0) - 180.0
+        };
+        Coordinates::validated(latitude.clamp(-90.0, 90.0), longitude).map_err(|e| format_err!("{}", e))
+    }
+
+    /// Extracts the coordinates from a Google Maps URL
+    ///
+    /// Recognizes a place pin (`!3d37.42!4d-122.08`), then a `q`, `ll`, `query` or `center` parameter
+    /// (`?q=37.42,-122.08`), then the map center (`@37.42,-122.08,15z`), in that order.
+    /// Fails if the URL has none of them, eg if it only searches for an address.
+    pub fn from_maps_url(url: &str) -> Result<Self> {
+        let parsed = Url::parse(url.trim())?;
+        let path = parsed.path();
+        let pin = path.find("!3d").and_then(|i| {
+            let mut parts = path[i + 3..].split('!');
+            let latitude = parts.next()?;
+            let longitude = parts.next()?.trim_start_matches("4d");
+            format!("{},{}", latitude, longitude).parse().ok()
+        });
+        let parameter = || parsed.query_pairs()
+            .filter(|(k, _)| ["q", "ll", "query", "center"].contains(&k.as_ref()))
+            .filter_map(|(_, v)| v.parse().ok())
+            .next();
+        let center = || path.find('@').and_then(|i| {
+            let mut parts = path[i + 1..].split(',');
+            format!("{},{}", parts.next()?, parts.next()?).parse().ok()
+        });
+        pin.or_else(parameter)
+            .or_else(center)
+            .ok_or_else(|| format_err!("No coordinates found in \"{}\"", url))
+    }
+
+    /// The number of decimal places sent to the API, about 11mm at the equator
+    pub const QUERY_PRECISION: usize = 7;
+
+    /// Formats these coordinates as `lat,lng` rounded to at most the specified number of decimal places
+    ///
+    /// Trailing zeros are dropped, so `to_precision(7)` of 48.5,2.25 is `48.5,2.25`.
+    pub fn to_precision(&self, digits: usize) -> String {
+        let round = |degrees: f64| {
+            let s = format!("{:.*}", digits, degrees);
+            if s.contains('.') {
+                s.trim_end_matches('0').trim_end_matches('.').to_string()
+            } else {
+                s
+            }
+        };
+        format!("{},{}", round(self.latitude_degrees()), round(self.longitude_degrees()))
+    }
+
+    /// The great circle distance to `other` in metres, ignoring altitude
+    ///
+    /// This treats the earth as a sphere of the WGS-84 equatorial radius, which is what to use for distances along the ground.
+    /// Unlike `WGS84::distance`, which this dereferences to, any difference in altitude is not added.
+    pub fn distance_2d(&self, other: &Coordinates) -> f64 {
+        let flatten = |c: &Coordinates| WGS84::new(c.latitude_degrees(), c.longitude_degrees(), 0f64);
+        flatten(self).distance(&flatten(other))
+    }
+
+    /// The compass bearing in degrees, from 0 (north) clockwise to 360, at which to set off along the great circle to `other`
+    ///
+    /// The bearing changes along the way, see `final_bearing_to`. It is 0 if both coordinates are the same.
+    pub fn initial_bearing_to(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude_degrees().to_radians(), other.latitude_degrees().to_radians());
+        let delta_lng = (other.longitude_degrees() - self.longitude_degrees()).to_radians();
+        let y = delta_lng.sin() * lat2.cos();
+        let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * delta_lng.cos();
+        y.atan2(x).to_degrees().rem_euclid(360.0)
+    }
+
+    /// The compass bearing in degrees, from 0 (north) clockwise to 360, at which the great circle from here arrives at `other`
+    pub fn final_bearing_to(&self, other: &Coordinates) -> f64 {
+        (other.initial_bearing_to(self) + 180.0).rem_euclid(360.0)
+    }
+
+    /// The straight line distance to `other` in metres, including altitude
+    ///
+    /// This is measured through the earth between ECEF positions,
+    /// so it is shorter than `distance_2d` over long distances, and only differs from it over short ones if the altitudes differ.
+    pub fn distance_3d(&self, other: &Coordinates) -> f64 {
+        let (a, b) = (self.to_ecef(), other.to_ecef());
+        let (dx, dy, dz) = (a.x() - b.x(), a.y() - b.y(), a.z() - b.z());
+        (dx * dx + dy * dy + dz * dz).sqrt()
+    }
+
+    /// The earth-centered, earth-fixed position of these coordinates, in metres
+    ///
+    /// Unlike latitude and longitude, ECEF positions support vector math such as midpoints and interpolation.
+    pub fn to_ecef(&self) -> ECEF<f64> {
+        ECEF::from(self.0)
+    }
+
+    /// A GeoJSON `Point` geometry at these coordinates
+    ///
+    /// Note that GeoJSON positions are ordered `[longitude, latitude]`.
+    pub fn to_geojson_point(&self) -> serde_json::Value {
+        json!({
+            "type": "Point",
+            "coordinates": [self.longitude_degrees(), self.latitude_degrees()],
+        })
+    }
+}
+
+/// Parses coordinates in the `lat,lng` form produced by `Display`
+impl FromStr for Coordinates {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.split(',');
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(latitude), Some(longitude), None) => {
+                let latitude = latitude.trim().parse::<f64>()
+                    .map_err(|e| format_err!("Invalid latitude \"{}\": {}", latitude, e))?;
+                let longitude = longitude.trim().parse::<f64>()
+                    .map_err(|e| format_err!("Invalid longitude \"{}\": {}", longitude, e))?;
+                Coordinates::validated(latitude, longitude).map_err(|e| format_err!("{}", e))
+            },
+            _ => Err(format_err!("Expected coordinates in the form \"lat,lng\", got \"{}\"", s)),
+        }
+    }
+}
+
+/// Serializes as the `lat,lng` query parameter, rounded to `QUERY_PRECISION`
+impl Serialize for Coordinates {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
+        serializer.serialize_str(&self.to_precision(Self::QUERY_PRECISION))
+    }
+}
+
+impl std::fmt::Display for Coordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{},{}", self.0.latitude_degrees(), self.0.longitude_degrees())
+    }
+}
+
+impl From<WGS84<f64>> for Coordinates {
+    fn from(v: WGS84<f64>) -> Self {
+        Coordinates(v)
+    }
+}
+
+/// Widens single precision coordinates, as commonly produced by GPS sensors
+///
+/// Widening is lossless, but `f32` only resolves about a metre at these magnitudes,
+/// so the extra digits of the result carry no real precision.
+impl From<WGS84<f32>> for Coordinates {
+    fn from(v: WGS84<f32>) -> Self {
+        Coordinates(WGS84::new(
+            f64::from(v.latitude_degrees()),
+            f64::from(v.longitude_degrees()),
+            f64::from(v.altitude()),
+        ))
+    }
+}
+
+
+#[derive(Clone, Debug)]
+/// A query for an address
+///
+/// The `result_type` and `location_type` filters only apply to reverse geocoding,
+/// so they are only offered here and not on `GeocodeQuery`.
+/// Conversely, the components, bounds and region biasing parameters only apply to forward geocoding;
+/// the API has no biasing parameters for reverse geocoding, so none are offered here.
+pub struct DegeocodeQuery {
+    /// The latitude and longitude values specifying the location for which you wish to obtain the closest, human-readable address.
+    coordinates: Coordinates,
+
+    /// The number of decimal places to which `coordinates` are rounded when sent.
+    coordinate_precision: usize,
+
+    /// Parameters not modelled by this crate, sent as is.
+    extra_params: BTreeMap<String, String>,
+
+    /// The language in which to return results.
+    language: Option<LanguageCode>,
+
+    /// A filter of one or more address types.
+    ///
+    /// If the parameter contains multiple address types, the API returns all addresses that match any of the types.
+    ///
+    /// A note about processing: The result_type parameter does not restrict the search to the specified address type(s). Rather, the result_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified address type(s).
+    ///
+    /// Note: This parameter is available only for requests that include an API key or a client ID.
+    result_type: Option<ApiSet<Type>>,
+
+    /// A filter of one or more location types.
+    ///
+    /// If the parameter contains multiple location types, the API returns all addresses that match any of the types.
+    ///
+    /// A note about processing: The location_type parameter does not restrict the search to the specified location type(s). Rather, the location_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified location type(s).
+    ///
+    /// Note: This parameter is available only for requests that include an API key or a client ID.
+    location_type: Option<ApiSet<LocationType>>,
+}
+
+impl DegeocodeQuery {
+    /// Creates a new address query
+    pub fn new(coordinates: impl Into<Coordinates>) -> Self {
+        DegeocodeQuery {
+            coordinates: coordinates.into(),
+            coordinate_precision: Coordinates::QUERY_PRECISION,
+            extra_params: BTreeMap::new(),
+            language: None,
+            location_type: None,
+            result_type: None,
+        }
+    }
+
+    /// Creates a new address query from a latitude and longitude in degrees
+    ///
+    /// Fails if the coordinates are not finite or do not lie on the WGS-84 ellipsoid.
+    pub fn from_lat_lng(latitude: f64, longitude: f64) -> Result<Self> {
+        Coordinates::validated(latitude, longitude)
+            .map(Self::new)
+            .map_err(|e| format_err!("{}", e))
+    }
+
+    /// The number of decimal places to which the coordinates are rounded when sent, `QUERY_PRECISION` by default
+    ///
+    /// Fewer digits keep more of the location private, eg 3 decimal places is about 110m at the equator.
+    pub fn coordinate_precision(mut self, i_digits: usize) -> Self {
+        self.coordinate_precision = i_digits;
+        self
+    }
+
+    /// A parameter not modelled by this crate, see `GeocodeQuery::extra_param`
+    pub fn extra_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_params.insert(key.into(), value.into());
+        self
+    }
+
+    /// The language in which to return results.
+    pub fn language(mut self, i_language: impl Into<LanguageCode>) -> Self {
+        self.language = Some(i_language.into());
+        self
+    }
+
+    /// A filter of one or more location types.
+    ///
+    /// If the parameter contains multiple location types, the API returns all addresses that match any of the types.
+    ///
+    /// A note about processing: The location_type parameter does not restrict the search to the specified location type(s). Rather, the location_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified location type(s).
+    ///
+    /// Note: This parameter is available only for requests that include an API key or a client ID.
+    pub fn location_type(mut self, i_location_type: ApiSet<LocationType>) -> Self {
+        self.location_type = Some(i_location_type);
+        self
+    }
+
+    /// A filter of one or more location types, see `location_type`
+    pub fn location_types(self, i_location_types: impl IntoIterator<Item = LocationType>) -> Self {
+        self.location_type(i_location_types.into_iter().collect())
+    }
+
+    /// A filter of one or more address types.
+    ///
+    /// If the parameter contains multiple address types, the API returns all addresses that match any of the types.
+    ///
+    /// A note about processing: The result_type parameter does not restrict the search to the specified address type(s). Rather, the result_type acts as a post-search filter: the API fetches all results for the specified latlng, then discards those results that do not match the specified address type(s).
+    ///
+    /// Note: This parameter is available only for requests that include an API key or a client ID.
+    pub fn result_type(mut self, i_result_type: ApiSet<Type>) -> Self {
+        self.result_type = Some(i_result_type);
+        self
+    }
+
+    /// A filter of one or more address types, see `result_type`
+    pub fn result_types(self, i_result_types: impl IntoIterator<Item = Type>) -> Self {
+        self.result_type(i_result_types.into_iter().collect())
+    }
+
+    /// Whether the reply passes the `result_type` and `location_type` filters, if any
+    ///
+    /// A reply passes a filter if it has any of its types.
+    pub fn matches(&self, reply: &Reply) -> bool {
+        let result_type = self.result_type.as_ref()
+            .map_or(true, |types| reply.types.iter().any(|t| types.contains(t)));
+        let location_type = self.location_type.as_ref()
+            .map_or(true, |types| types.contains(&reply.geometry.location_type));
+        result_type && location_type
+    }
+
+    /// Keep only the replies that pass the filters, see `matches`
+    ///
+    /// The API only applies the filters to requests with an API key, so this applies them client-side too.
+    /// Unlike the API, which replies `ZeroResults` if no result passes, this leaves no replies.
+    pub fn retain_matching(&self, mut replies: Vec<Reply>) -> Vec<Reply> {
+        replies.retain(|reply| self.matches(reply));
+        replies
+    }
+}
+
+impl<T> From<T> for DegeocodeQuery where Coordinates: From<T> {
+    fn from(v: T) -> Self {
+        Self::new(v)
+    }
+}
+
+/// Parses coordinates in the `lat,lng` form, failing rather than treating the string as an address
+///
+/// For example `degeocode(DegeocodeQuery::try_from("37.42241,-122.08561")?)`.
+impl<'a> TryFrom<&'a str> for DegeocodeQuery {
+    type Error = Error;
+
+    fn try_from(s: &'a str) -> Result<Self> {
+        s.parse::<Coordinates>().map(Self::new)
+    }
+}
+
+/// Serializes as query parameters, leaving out unset ones
+impl Serialize for DegeocodeQuery {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
+        use serde::ser::SerializeMap;
+        let mut s = serializer.serialize_map(None)?;
+        s.serialize_entry("latlng", &self.coordinates.to_precision(self.coordinate_precision))?;
+        if let Some(ref language) = self.language {
+            s.serialize_entry("language", language)?;
+        }
+        if let Some(ref result_type) = self.result_type {
+            s.serialize_entry("result_type", result_type)?;
+        }
+        if let Some(ref location_type) = self.location_type {
+            s.serialize_entry("location_type", location_type)?;
+        }
+        for (key, value) in &self.extra_params {
+            s.serialize_entry(key, value)?;
+        }
+        s.end()
+    }
+}
+
+impl ApiQuery for DegeocodeQuery{}
+
+/// A query for the place with the specified ID
+#[derive(Debug, Serialize)]
+struct PlaceIdQuery {
+    place_id: PlaceId,
+}
+
+impl ApiQuery for PlaceIdQuery{}
+
+/// A query for coordinates
+///
+/// The default query is empty, so that it can be assembled field by field.
+/// Note that the API requires at least an address or a components filter.
+///
+/// The API does not support the `result_type` and `location_type` filters when geocoding forward,
+/// so they are only offered on `DegeocodeQuery`.
+/// To narrow forward results, use a components filter, or filter the replies client-side with `ReplyIterator`.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GeocodeQuery {
+    /// The street address that you want to geocode.
+    #[serde(skip_serializing_if="Option::is_none")]
+    address: Option<String>,
+
+    /// A components filter that fully restricts the results from the geocoder.
+    #[serde(skip_serializing_if="Option::is_none")]
+    components: Option<ApiSet<ComponentFilterRule>>,
+
+    /// The bounding box of the viewport within which to bias geocode results more prominently.
+    /// This parameter will only influence, not fully restrict, results from the geocoder.
+    /// (For more information see Viewport Biasing below.)
+    #[serde(serialize_with="serialize_bounds", skip_serializing_if="Option::is_none")]
+    bounds: Option<Viewport>,
+
+    /// The language in which to return results.
+    #[serde(skip_serializing_if="Option::is_none")]
+    language: Option<LanguageCode>,
+
+    /// The region code.
+    ///
+    /// This parameter will only influence, not fully restrict, results from the geocoder.
+    /// (For more information see Region Biasing below.)
+    ///
+    /// When None, no `region` parameter is sent at all, so no region bias is requested.
+    #[serde(skip_serializing_if="Option::is_none")]
+    region: Option<RegionCode>,
+
+    /// A token grouping related requests into a single billing session.
+    #[serde(rename="sessiontoken", skip_serializing_if="Option::is_none")]
+    session_token: Option<String>,
+
+    /// Languages to retry in, in order, if there are no results in `language`.
+    #[serde(skip)]
+    retry_languages: Vec<LanguageCode>,
+
+    /// Whether diacritics are stripped from the address.
+    #[serde(skip)]
+    normalize_diacritics: bool,
+
+    /// Parameters not modelled by this crate, sent as is.
+    #[serde(flatten)]
+    extra_params: BTreeMap<String, String>,
+}
+
+impl GeocodeQuery {
+    /// Creates a new coordinates query
+    pub fn new(filter: impl Into<Place>) -> Self {
+        let query = GeocodeQuery::default();
+        match filter.into() {
+            Place::Address { address } => query.address(address),
+            Place::ComponentFilter { components } => query.components(components),
+        }
+    }
+
+    /// The street address that you want to geocode.
+    ///
+    /// Its whitespace is normalized: lines are trimmed, runs of whitespace collapsed, and line breaks replaced by commas.
+    pub fn address(mut self, i_address: impl Into<String>) -> Self {
+        let address = normalize_address(&i_address.into());
+        self.address = Some(if self.normalize_diacritics { strip_diacritics(&address) } else { address });
+        self
+    }
+
+    /// Whether to strip diacritics from the address, eg `Zürich` becomes `Zurich`
+    ///
+    /// The geocoder handles accents well, so this is only useful to match addresses stored without them.
+    /// Letters that are not decomposed by Unicode normalization, such as `ß` or `ø`, are left as is.
+    /// Enabling this strips the address already set as well as any set afterwards,
+    /// but disabling it does not restore the accents.
+    pub fn normalize_diacritics(mut self, i_normalize_diacritics: bool) -> Self {
+        self.normalize_diacritics = i_normalize_diacritics;
+        if i_normalize_diacritics {
+            self.address = self.address.map(|x| strip_diacritics(&x));
+        }
+        self
+    }
+
+    /// A components filter that fully restricts the results from the geocoder.
+    ///
+    /// This may be combined with an address, in which case the address is only searched within the filter.
+    pub fn components(mut self, i_components: impl Into<ApiSet<ComponentFilterRule>>) -> Self {
+        self.components = Some(i_components.into());
+        self
+    }
+
+    /// The bounding box of the viewport within which to bias geocode results more prominently.
+    /// This parameter will only influence, not fully restrict, results from the geocoder.
+    /// (For more information see Viewport Biasing below.)
+    pub fn bounds(mut self, i_bounds: Viewport) -> Self {
+        self.bounds = Some(i_bounds);
+        self
+    }
+
+    /// The language in which to return results.
+    pub fn language(mut self, i_language: impl Into<LanguageCode>) -> Self {
+        self.language = Some(i_language.into());
+        self
+    }
+
+    /// The languages in which to return results, in order of preference
+    ///
+    /// The first language is requested, and the query is only sent again in the next language if the API finds no results.
+    /// Results are not re-queried for being in another language than the one requested: the API does not report
+    /// which language it answered in, and uses the local language by itself when a translation is missing.
+    pub fn language_preference<L>(mut self, i_languages: &[L]) -> Self where L: Clone + Into<LanguageCode> {
+        let mut languages = i_languages.iter().cloned().map(L::into);
+        if let Some(first) = languages.next() {
+            self.language = Some(first);
+            self.retry_languages = languages.collect();
+        }
+        self
+    }
+
+    /// The region code.
+    ///
+    /// This parameter will only influence, not fully restrict, results from the geocoder.
+    /// (For more information see Region Biasing below.)
+    ///
+    /// By default no region is set, and the `region` parameter is omitted from the request entirely
+    /// rather than sent with a default value.
+    pub fn region(mut self, i_region: impl Into<RegionCode>) -> Self {
+        self.region = Some(i_region.into());
+        self
+    }
+
+    /// A token grouping related requests into a single billing session.
+    ///
+    /// This is used by the Places autocomplete flow; the classic geocoding endpoint ignores it,
+    /// but sending it lets geocoding requests made as part of such a flow share its token.
+    pub fn session_token(mut self, i_session_token: impl Into<String>) -> Self {
+        self.session_token = Some(i_session_token.into());
+        self
+    }
+
+    /// A parameter not modelled by this crate, eg one the API has only just introduced
+    ///
+    /// The parameter is sent after the typed ones, and replaces any extra parameter previously set with the same key.
+    /// It is not checked against the typed parameters, so setting eg `language` here as well sends it twice.
+    pub fn extra_param(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.extra_params.insert(key.into(), value.into());
+        self
+    }
+}
+
+impl ApiQuery for GeocodeQuery {
+    fn validate(&self) -> std::result::Result<(), InvalidQuery> {
+        match (&self.address, &self.components) {
+            (None, None) => return Err(InvalidQuery::MissingAddressAndComponents),
+            (Some(address), _) if address.trim().is_empty() => return Err(InvalidQuery::EmptyAddress),
+            (_, Some(components)) if components.is_empty() => return Err(InvalidQuery::NoComponentFilters),
+            _ => (),
+        }
+        for rule in self.components.iter().flat_map(|c| c.iter()) {
+            rule.validate()?;
+        }
+        Ok(())
+    }
+}
+
+/// Serializes bounds as `southwest|northeast`, the format expected by the API
+fn serialize_bounds<S>(bounds: &Option<Viewport>, serializer: S) -> std::result::Result<S::Ok, S::Error> where S: Serializer {
+    match bounds {
+        Some(v) => serializer.serialize_str(&format!("{}|{}",
+            v.southwest.to_precision(Coordinates::QUERY_PRECISION),
+            v.northeast.to_precision(Coordinates::QUERY_PRECISION))),
+        None => serializer.serialize_none(),
+    }
+}
+
+impl<T> From<T> for GeocodeQuery where Place: From<T> {
+    fn from(v: T) -> Self {
+        Self::new(v)
+    }
+}
+
+/// An address in one of various formats
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum Place {
+    /// A specific place
+    Address {
+        /// The street address that you want to geocode,
+        /// in the format used by the national postal service of the country concerned.
+        /// Additional address elements such as business names and unit, suite or floor numbers should be avoided.
+        /// Please refer to the FAQ for additional guidance. 
+        address: String
+    },
+    /// The address broken down into components
+    ComponentFilter {
+        /// The components filter is required if the request doesn't include an address.
+        /// Each element in the components filter consists of a component:value pair,
+        /// and fully restricts the results from the geocoder.
+        /// See more information about component filtering below.
+        components: ApiSet<ComponentFilterRule>
+    },
+}
+
+impl Place {
+    /// The intersection of two routes, eg "Hollywood Blvd" and "Vine St"
+    ///
+    /// The route names are trimmed and joined with `&`, the form the geocoder recognizes as an intersection.
+    /// Returns None if either route name is empty.
+    pub fn intersection(first_route: &str, second_route: &str) -> Option<Self> {
+        let (first, second) = (first_route.trim(), second_route.trim());
+        if first.is_empty() || second.is_empty() {
+            return None;
+        }
+        Some(Place::Address {
+            address: format!("{} & {}", first, second)
+        })
+    }
+}
+
+/// Prints the address, or the component filter rules as they are sent to the API, eg `components=country:FR|postal_code:75001`
+impl Display for Place {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Place::Address { address } => Display::fmt(address, f),
+            Place::ComponentFilter { components } => {
+                let rules = serde_util::to_plain_string(components).map_err(|_| fmt::Error)?;
+                write!(f, "components={}", rules)
+            },
+        }
+    }
+}
+
+/// Addresses are normalized, see `normalize_address`
+impl<T> From<T> for Place where T: Into<String> {
+    fn from(s: T) -> Self {
+        Place::Address { 
+            address: normalize_address(&s.into())
+        }
+    }
+}
+
+/// Normalizes the whitespace of an address, eg one pasted from a multi-line form input
+///
+/// Each line is trimmed and its runs of whitespace collapsed to a single space,
+/// then the non-empty lines are joined with commas.
+fn normalize_address(address: &str) -> String {
+    use itertools::Itertools;
+    address.lines()
+        .map(|line| line.split_whitespace().join(" "))
+        .map(|line| line.trim_end_matches(',').to_string())
+        .filter(|line| !line.is_empty())
+        .join(", ")
+}
+
+/// Strips the combining marks left after canonical decomposition, then recomposes the rest
+fn strip_diacritics(address: &str) -> String {
+    use unicode_normalization::UnicodeNormalization;
+    use unicode_normalization::char::is_combining_mark;
+    address.nfd()
+        .filter(|&c| !is_combining_mark(c))
+        .nfc()
+        .collect()
+}
+
+/// A unique identifier that can be used with other Google APIs.
+/// For example, you can use the place_id in a Places SDK request to get details of a local business, such as phone number, opening hours, user reviews, and more. See the place ID overview.
+#[derive(Clone,Debug,Deserialize,Eq,Hash,PartialEq,Serialize)]
+pub struct PlaceId(String);
+
+impl<T> From<T> for PlaceId where T: Into<String> {
+    fn from(s: T) -> Self {
+        PlaceId(s.into())
+    }
+}
+
+/// Get all the coordinates associated with the specified filter
+pub fn geocode(address: impl Into<GeocodeQuery>) -> Result<impl ExactSizeIterator<Item=Coordinates>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    Ok(core.run(Connection::new(&core_handle).geocode(address))?.into_iter().map(|x|x.geometry.location))
+}
+
+/// Get all the coordinates associated with the specified filter, failing if they take longer than `timeout`
+pub fn geocode_timeout(address: impl Into<GeocodeQuery>, timeout: Duration) -> Result<impl ExactSizeIterator<Item=Coordinates>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    let replies = with_timeout(Connection::new(&core_handle).geocode(address), timeout, &core_handle)?;
+    Ok(core.run(replies)?.into_iter().map(|x|x.geometry.location))
+}
+
+/// Wrap a future so that it fails if it does not resolve within `timeout`
+fn with_timeout<T>(future: impl Future<Item = T, Error = Error>, timeout: Duration, handle: &tokio_core::reactor::Handle) -> Result<impl Future<Item = T, Error = Error>> {
+    let timer = Timeout::new(timeout, handle)?
+        .map_err(Error::from)
+        .and_then(move |_| Err(format_err!("Request timed out after {:?}", timeout)));
+    Ok(future.select(timer).map(|(x, _)| x).map_err(|(e, _)| e))
+}
+
+/// Get the full replies associated with the specified filter
+///
+/// Unlike `geocode`, this returns the replies themselves as a concrete collection.
+pub fn try_geocode(address: impl Into<GeocodeQuery>) -> Result<Vec<Reply>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    core.run(Connection::new(&core_handle).geocode(address))
+}
+
+/// Get the most relevant result that is not a partial match and not `Approximate` or `Unknown`
+///
+/// Returns None rather than a questionable result if there is no confident match.
+pub fn geocode_best_confident(address: impl Into<GeocodeQuery>) -> Result<Option<Reply>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    let replies = core.run(Connection::new(&core_handle).geocode(address))?;
+    Ok(replies.best_confident().cloned())
+}
+
+/// Get the localities within the postal code of the most relevant result, eg the cities sharing a ZIP code
+///
+/// The API only lists them when the result is a postal code containing multiple localities,
+/// so this returns None otherwise.
+pub fn geocode_postcode_localities(address: impl Into<GeocodeQuery>) -> Result<Option<Vec<String>>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    let replies = core.run(Connection::new(&core_handle).geocode(address))?;
+    Ok(replies.into_iter().next().and_then(|x| x.postcode_localities))
+}
+
+/// Get the place ID and coordinates of every result associated with the specified filter
+pub fn geocode_id_and_coords(address: impl Into<GeocodeQuery>) -> Result<impl ExactSizeIterator<Item=(PlaceId, Coordinates)>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    Ok(core.run(Connection::new(&core_handle).geocode(address))?.into_iter().map(|x|(x.place_id, x.geometry.location)))
+}
+
+/// Get all the coordinates associated with the specified filter, biased towards results in the specified region
+pub fn geocode_in_region(address: impl Into<Place>, region: impl Into<RegionCode>) -> Result<impl ExactSizeIterator<Item=Coordinates>> {
+    geocode(GeocodeQuery::new(address).region(region))
+}
+
+/// Get all the addresses associated with the specified coordinates
+pub fn degeocode(coordinates: impl Into<DegeocodeQuery>) -> Result<impl ExactSizeIterator<Item=FormattedAddress>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    Ok(core.run(Connection::new(&core_handle).degeocode(coordinates))?.into_iter().map(|x|x.formatted_address))
+}
+
+/// Get the full replies associated with the specified coordinates
+///
+/// Unlike `degeocode`, this returns the replies themselves as a concrete collection.
+pub fn try_degeocode(coordinates: impl Into<DegeocodeQuery>) -> Result<Vec<Reply>> {
+    let mut core = Core::new()?;
+    let core_handle = core.handle();
+    core.run(Connection::new(&core_handle).degeocode(coordinates))
+}
+
+/// Get all the addresses associated with the specified coordinates, in the specified language
+pub fn degeocode_in_language(coordinates: impl Into<Coordinates>, language: impl Into<LanguageCode>) -> Result<impl ExactSizeIterator<Item=FormattedAddress>> {
+    degeocode(DegeocodeQuery::new(coordinates).language(language))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    const ADDRESS: &str = "1600 Amphitheatre Pkwy, Mountain View, CA 94043, USA";
+    const COORDINATES: (f64, f64) = (37.42241, -122.08561);
+    const GEOCODE_FIXTURE: &str = include_str!("../tests/fixtures/geocode.json");
+    const DEGEOCODE_FIXTURE: &str = include_str!("../tests/fixtures/degeocode.json");
+    const GEOCODE_PARIS_FIXTURE: &str = include_str!("../tests/fixtures/geocode_paris.json");
+    const GEOCODE_NO_VIEWPORT_FIXTURE: &str = include_str!("../tests/fixtures/geocode_no_viewport.json");
+    const GEOCODE_INTERSECTION_FIXTURE: &str = include_str!("../tests/fixtures/geocode_intersection.json");
+
+    /// An HTTP response carrying the specified JSON body
+    fn ok_response(body: &str) -> String {
+        format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body)
+    }
+
+    /// Serves the specified raw HTTP responses, one per request, on a local port
+    fn serve_responses(responses: Vec<String>) -> Url {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind fixture server");
+        let url = Url::parse(&format!("http://{}/json", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            for response in responses {
+                let (stream, _) = listener.accept().expect("Failed to accept fixture request");
+                let mut reader = BufReader::new(stream);
+                let mut line = String::new();
+                while reader.read_line(&mut line).map(|n| n > 0).unwrap_or(false) && line != "\r\n" {
+                    line.clear();
+                }
+                reader.into_inner().write_all(response.as_bytes()).unwrap();
+            }
+        });
+        url
+    }
+
+    /// A connection to a local server serving the specified raw HTTP responses, one per request
+    fn fixture_connection(responses: Vec<String>) -> (Core, Connection) {
+        let core = Core::new().expect("Failed to initialize core");
+        let connection = Connection::with_insecure_base_url(&core.handle(), serve_responses(responses));
+
+        (core, connection)
+    }
+
+    fn fixture_start(body: &'static str) -> (Core, Connection) {
+        fixture_connection(vec![ok_response(body)])
+    }
+
+    fn test_print<L>(i_label: L, i_rr: impl Future<Item = Vec<Reply>, Error=Error>) -> impl Future<Item=(),Error=()> 
+        where L: Debug + 'static {
+        let label = i_label;
+        i_rr
+            .map(move |rr| println!("{:#?}: {:#?}", label, rr.iter().map(|r|&r.geometry.location).collect::<Vec<_>>()))
+            .map_err(|e| error!("{:?}", e))
+
+    }
+
+    fn test_start() -> (Core, Connection) {
+        let core = Core::new().expect("Failed to initialize core");
+        let connection = Connection::new(&core.handle());
+
+        (core, connection)
+    }
+
+    fn test_stop<T>(mut i_core: Core, i_future: T)
+        where T: futures::Future<Item=(), Error=()> {
+        i_core.run(i_future).expect("Failed running tests")
+    }
+
+    #[test]
+    fn address() {
+        super::degeocode(WGS84::try_new(COORDINATES.0, COORDINATES.1, 0.0).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn coordinates() {
+        super::geocode(ADDRESS).unwrap();
+    }
+
+    #[test]
+    fn connection_both() {
+        let (core, connection) = test_start();
+        let tests = futures::Future::join(
+            test_print("Basic", connection.geocode(ADDRESS)),
+            test_print("Basic", connection.degeocode(
+                WGS84::new(COORDINATES.0, COORDINATES.1, 0f64)
+            )),
+        ).map(|_|());
+        test_stop(core, tests)
+    }
+
+    #[test]
+    fn compare() {
+        let mut coordinates = super::geocode(ADDRESS).unwrap();
+        let first_coordinates = coordinates.next().unwrap();
+        let mut addresses = super::degeocode(first_coordinates).unwrap();
+        let first_address = addresses.next().unwrap();
+        assert_eq!(ADDRESS, first_address.0);
+    }
+
+    #[test]
+    fn component_filter_multiple() {
+        let components = ApiSet(vec![
+            ComponentFilterRule::Country("US".to_string()),
+            ComponentFilterRule::AdministrativeArea("CA".to_string()),
+        ].into_iter().collect());
+        let query = GeocodeQuery::new(Place::ComponentFilter { components });
+        let encoded = serde_urlencoded::to_string(&query).unwrap();
+        let params: Vec<(String, String)> = serde_urlencoded::from_str(&encoded).unwrap();
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0].0, "components");
+        let mut rules = params[0].1.split('|').collect::<Vec<_>>();
+        rules.sort();
+        assert_eq!(rules, vec!["administrative_area:CA", "country:US"]);
+    }
+
+    fn coordinates_from_pair(lat: f64, lng: f64) -> std::result::Result<Coordinates, serde::de::value::Error> {
+        use serde::de::value::MapDeserializer;
+        Coordinates::deserialize(MapDeserializer::new(vec![("lat", lat), ("lng", lng)].into_iter()))
+    }
+
+    #[test]
+    fn coordinates_not_finite() {
+        let nan = coordinates_from_pair(f64::NAN, 0.0).unwrap_err();
+        assert!(nan.to_string().contains("not finite"));
+        let infinite = coordinates_from_pair(0.0, f64::INFINITY).unwrap_err();
+        assert!(infinite.to_string().contains("not finite"));
+    }
+
+    #[test]
+    fn coordinates_out_of_range() {
+        let latitude = coordinates_from_pair(90.5, 0.0).unwrap_err();
+        assert!(latitude.to_string().contains("WGS-84"));
+        let longitude = coordinates_from_pair(0.0, 180.5).unwrap_err();
+        assert!(longitude.to_string().contains("WGS-84"));
+        assert!(coordinates_from_pair(COORDINATES.0, COORDINATES.1).is_ok());
+    }
+
+    #[test]
+    fn point_and_label() {
+        let reply = serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results.remove(0);
+        let (point, label): (Coordinates, FormattedAddress) = reply.into();
+        assert_eq!(point.latitude_degrees(), COORDINATES.0);
+        assert_eq!(point.longitude_degrees(), COORDINATES.1);
+        assert_eq!(label.0, ADDRESS);
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn languages_complete() {
+        let codes = Language::iter().map(|l| serde_util::to_plain_string(&l).unwrap()).collect::<HashSet<_>>();
+        assert_eq!(Language::iter().count(), 82);
+        assert_eq!(codes.len(), 82);
+    }
+
+    #[test]
+    fn types_complete() {
+        let names = Type::iter().map(|t| serde_util::to_plain_string(&t).unwrap()).collect::<HashSet<_>>();
+        assert_eq!(Type::iter().count(), 37);
+        assert_eq!(names.len(), 37);
+        assert_eq!(LocationType::iter().count(), 5);
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn regions_unique() {
+        let codes = Region::iter().map(|r| serde_util::to_plain_string(&r).unwrap()).collect::<HashSet<_>>();
+        assert_eq!(codes.len(), Region::iter().count());
+        assert_eq!(serde_util::to_plain_string(&Region::RepublicOfKorea).unwrap(), "kr");
+    }
+
+    #[test]
+    fn language_and_region_codes() {
+        assert_eq!(LanguageCode::new("zh-CN").unwrap().code(), "zh-CN");
+        assert!(LanguageCode::new("EN").is_err());
+        assert_eq!(RegionCode::new(".FR").unwrap().code(), "fr");
+        assert!(RegionCode::new("fra").is_err());
+        let query = GeocodeQuery::new("Paris").language(LanguageCode::new("fr").unwrap()).region(RegionCode::new("fr").unwrap());
+        assert_eq!(serde_urlencoded::to_string(&query).unwrap(), "address=Paris&language=fr&region=fr");
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn codes_from_enums() {
+        assert_eq!(LanguageCode::from(Language::ChineseSimplified), LanguageCode::new("zh-CN").unwrap());
+        assert_eq!(RegionCode::from(Region::RepublicOfKorea).code(), "kr");
+        let query = GeocodeQuery::new("Paris").language(Language::French).region(Region::France);
+        assert_eq!(serde_urlencoded::to_string(&query).unwrap(), "address=Paris&language=fr&region=fr");
+    }
+
+    #[test]
+    fn fixture_geocode() {
+        let (mut core, connection) = fixture_start(GEOCODE_FIXTURE);
+        let replies = core.run(connection.geocode(ADDRESS)).unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+        assert_eq!(replies[0].geometry.location_type, LocationType::Rooftop);
+        assert_eq!(replies[0].geometry.location.latitude_degrees(), COORDINATES.0);
+        assert_eq!(replies[0].geometry.location.longitude_degrees(), COORDINATES.1);
+    }
+
+    #[test]
+    fn fixture_degeocode() {
+        let (mut core, connection) = fixture_start(DEGEOCODE_FIXTURE);
+        let coordinates = WGS84::try_new(COORDINATES.0, COORDINATES.1, 0.0).unwrap();
+        let replies = core.run(connection.degeocode(coordinates)).unwrap();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+        assert_eq!(replies[1].types, vec![Type::Locality, Type::Political]);
+        assert!(replies[1].geometry.bounds.is_some());
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn geocode_query_all_parameters() {
+        let components = ApiSet(vec![ComponentFilterRule::Country("FR".to_string())].into_iter().collect());
+        let bounds = Viewport {
+            northeast: WGS84::new(49.5, 3.5, 0.0).into(),
+            southwest: WGS84::new(48.5, 2.25, 0.0).into(),
+        };
+        let query = GeocodeQuery::new("Rue de Rivoli")
+            .components(components)
+            .bounds(bounds)
+            .region(Region::France);
+        let params: Vec<(String, String)> = serde_urlencoded::from_str(&serde_urlencoded::to_string(&query).unwrap()).unwrap();
+        assert_eq!(params, vec![
+            ("address".to_string(), "Rue de Rivoli".to_string()),
+            ("components".to_string(), "country:FR".to_string()),
+            ("bounds".to_string(), "48.5,2.25|49.5,3.5".to_string()),
+            ("region".to_string(), "fr".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn retry_after_seconds() {
+        let mut headers = Headers::new();
+        headers.set_raw("Retry-After", "120");
+        assert_eq!(Connection::retry_after(&headers), Some(Duration::from_secs(120)));
+        assert_eq!(Connection::retry_after(&Headers::new()), None);
+    }
+
+    #[test]
+    fn retry_after_date() {
+        let mut headers = Headers::new();
+        let at = SystemTime::now() + Duration::from_secs(300);
+        headers.set_raw("Retry-After", reqwest::header::HttpDate::from(at).to_string());
+        let delay = Connection::retry_after(&headers).unwrap();
+        assert!(delay <= Duration::from_secs(300) && delay >= Duration::from_secs(290));
+    }
+
+    #[test]
+    fn retry_delay_jitter() {
+        for attempt in 0..4 {
+            let base = Duration::from_millis(Connection::RETRY_BASE_DELAY_MS << attempt);
+            let delay = Connection::retry_delay(attempt, None);
+            assert!(delay >= base && delay <= base + base / 2);
+        }
+        let delay = Connection::retry_delay(0, Some(Duration::from_secs(2)));
+        assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(3));
+    }
+
+    #[test]
+    fn fixture_retry_too_many_requests() {
+        let (mut core, connection) = fixture_connection(vec![
+            "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+            ok_response(r#"{ "results": [], "status": "OVER_QUERY_LIMIT" }"#),
+            ok_response(GEOCODE_FIXTURE),
+        ]);
+        let connection = connection.retries(2);
+        let replies = core.run(connection.geocode(ADDRESS)).unwrap();
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+    }
+
+    #[test]
+    fn fixture_retry_unknown_error() {
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(r#"{ "results": [], "status": "UNKNOWN_ERROR" }"#),
+            ok_response(GEOCODE_FIXTURE),
+        ]);
+        let connection = connection.retries(1);
+        let replies = core.run(connection.geocode(ADDRESS)).unwrap();
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+        assert!(!StatusCode::RequestDenied.is_retryable());
+    }
+
+    #[test]
+    fn fixture_request_raw() {
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(r#"{ "error_message": "The provided API key is invalid.", "results": [], "status": "REQUEST_DENIED" }"#),
+        ]);
+        let connection = connection.retries(2);
+        let reply = core.run(connection.request_raw(GeocodeQuery::new(ADDRESS))).unwrap();
+        assert!(matches!(reply.status, StatusCode::RequestDenied));
+        assert_eq!(reply.error_message.as_deref(), Some("The provided API key is invalid."));
+        assert!(reply.results.is_empty());
+    }
+
+    #[test]
+    fn reply_status_errors() {
+        let error = |status: &str, message: Option<&str>| {
+            let body = json!({ "error_message": message, "results": [], "status": status });
+            serde_json::from_value::<ReplyResult>(body).unwrap().into_results().unwrap_err()
+        };
+        for &(status, message) in &[
+            ("ZERO_RESULTS", None),
+            ("OVER_QUERY_LIMIT", Some("You have exceeded your rate-limit for this API.")),
+            ("REQUEST_DENIED", Some("The provided API key is invalid.")),
+            ("INVALID_REQUEST", Some("Invalid request. Missing the 'address', 'components', 'latlng' or 'place_id' parameter.")),
+            ("UNKNOWN_ERROR", None),
+        ] {
+            let e = error(status, message);
+            let api_error = e.downcast_ref::<ApiError>().unwrap();
+            assert_eq!(serde_json::to_value(&api_error.status).unwrap(), status);
+            assert_eq!(api_error.error_message.as_deref(), message);
+            if let Some(message) = message {
+                assert!(e.to_string().ends_with(message));
+            }
+            assert_eq!(e.find_root_cause().downcast_ref::<StatusCode>().map(|s| s.to_string()), Some(api_error.status.to_string()));
+        }
+        assert!(matches!(error("OVER_QUERY_LIMIT", None).downcast_ref::<ApiError>(), Some(ApiError { status: StatusCode::OverQueryLimit, .. })));
+        assert!(matches!(error("REQUEST_DENIED", None).downcast_ref::<ApiError>(), Some(ApiError { status: StatusCode::RequestDenied, .. })));
+        assert!(matches!(error("INVALID_REQUEST", None).downcast_ref::<ApiError>(), Some(ApiError { status: StatusCode::InvalidRequest, .. })));
+        assert!(matches!(error("UNKNOWN_ERROR", None).downcast_ref::<ApiError>(), Some(ApiError { status: StatusCode::UnknownError, .. })));
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn fixture_language_preference() {
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(r#"{ "results": [], "status": "ZERO_RESULTS" }"#),
+            ok_response(GEOCODE_FIXTURE),
+        ]);
+        let query = GeocodeQuery::new(ADDRESS).language_preference(&[Language::French, Language::English]);
+        assert_eq!(serde_urlencoded::to_string(&query).unwrap(), serde_urlencoded::to_string(GeocodeQuery::new(ADDRESS).language(Language::French)).unwrap());
+        let replies = core.run(connection.geocode(query)).unwrap();
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+    }
+
+    #[test]
+    fn fixture_health_check() {
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(r#"{ "results": [], "status": "ZERO_RESULTS" }"#),
+            ok_response(r#"{ "error_message": "The provided API key is invalid.", "results": [], "status": "REQUEST_DENIED" }"#),
+        ]);
+        core.run(connection.health_check()).unwrap();
+        let error = core.run(connection.health_check()).unwrap_err();
+        match error.downcast_ref::<ApiError>() {
+            Some(ApiError { status: StatusCode::RequestDenied, .. }) => (),
+            e => panic!("Unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fixture_bom() {
+        let (mut core, connection) = fixture_start(include_str!("../tests/fixtures/geocode_bom.json"));
+        let replies = core.run(connection.geocode(ADDRESS)).unwrap();
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn fixture_geocode_multilang() {
+        let (mut core, connection) = fixture_connection(vec![ok_response(GEOCODE_FIXTURE), ok_response(GEOCODE_FIXTURE)]);
+        // The fixture server only answers twice, so the repeated language must not be requested again
+        let replies = core.run(connection.geocode_multilang(ADDRESS, &[Language::English, Language::Japanese, Language::English], 1)).unwrap();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[&LanguageCode::from(Language::Japanese)][0].formatted_address.0, ADDRESS);
+        assert!(replies.contains_key(&LanguageCode::from(Language::English)));
+    }
+
+    #[test]
+    fn fixture_geocode_raw_bytes() {
+        let (mut core, connection) = fixture_start(GEOCODE_FIXTURE);
+        let (replies, body) = core.run(connection.geocode_raw_bytes(ADDRESS)).unwrap();
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+        assert_eq!(body, GEOCODE_FIXTURE.as_bytes());
+    }
+
+    #[test]
+    fn fixture_timeout() {
+        // Accepts connections into the backlog but never replies
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!("http://{}/json", listener.local_addr().unwrap())).unwrap();
+        let mut core = Core::new().expect("Failed to initialize core");
+        let connection = Connection::with_insecure_base_url(&core.handle(), url);
+        let replies = with_timeout(connection.geocode(ADDRESS), Duration::from_millis(50), &core.handle()).unwrap();
+        let error = core.run(replies).unwrap_err();
+        assert!(error.to_string().contains("timed out"));
+    }
+
+    #[test]
+    fn fixture_geocode_batch_with_concurrency() {
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(GEOCODE_FIXTURE),
+            ok_response(GEOCODE_FIXTURE),
+        ]);
+        let results = core.run(connection.geocode_batch_with_concurrency(vec![ADDRESS, "", ADDRESS], 1)).unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap()[0].formatted_address.0, ADDRESS);
+        assert!(results[1].as_ref().unwrap_err().downcast_ref::<InvalidQuery>().is_some());
+        assert_eq!(results[2].as_ref().unwrap()[0].formatted_address.0, ADDRESS);
+    }
+
+    #[test]
+    fn fixture_retries_exhausted() {
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(r#"{ "results": [], "status": "OVER_QUERY_LIMIT" }"#),
+        ]);
+        let error = core.run(connection.geocode(ADDRESS)).unwrap_err();
+        match error.downcast_ref::<ApiError>() {
+            Some(ApiError { status: StatusCode::OverQueryLimit, .. }) => (),
+            e => panic!("Unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn fixture_daily_quota_not_retried() {
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(r#"{ "error_message": "You have exceeded your daily request quota for this API.", "results": [], "status": "OVER_QUERY_LIMIT" }"#),
+        ]);
+        let connection = connection.retries(3);
+        let error = core.run(connection.geocode(ADDRESS)).unwrap_err();
+        let api_error = error.downcast_ref::<ApiError>().unwrap();
+        assert_eq!(api_error.quota_limit(), Some(QuotaLimit::DailyQuotaExceeded));
+        assert!(!api_error.is_retryable());
+
+        let rate_limited = ApiError { status: StatusCode::OverQueryLimit, error_message: Some("You have exceeded your rate-limit for this API.".to_string()) };
+        assert_eq!(rate_limited.quota_limit(), Some(QuotaLimit::RateLimited));
+        assert!(rate_limited.is_retryable());
+        assert_eq!(ApiError { status: StatusCode::RequestDenied, error_message: None }.quota_limit(), None);
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn dry_run() {
+        let core = Core::new().expect("Failed to initialize core");
+        let connection = Connection::new(&core.handle());
+        let url = connection.dry_run(GeocodeQuery::new(ADDRESS).language(Language::German)).unwrap();
+        assert_eq!(url.as_str(), "https://maps.google.com/maps/api/geocode/json?address=1600+Amphitheatre+Pkwy%2C+Mountain+View%2C+CA+94043%2C+USA&language=de");
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn request_url() {
+        let base = Url::parse("https://maps.google.com/maps/api/geocode/json").unwrap();
+        let url = build_request_url(&base, &GeocodeQuery::new("Rue de Rivoli").region(Region::France), Some("abc 123")).unwrap();
+        assert_eq!(url.as_str(), "https://maps.google.com/maps/api/geocode/json?address=Rue+de+Rivoli&region=fr&key=abc+123");
+        let url = build_request_url(&base, &DegeocodeQuery::from_lat_lng(48.5, 2.25).unwrap(), None).unwrap();
+        assert_eq!(url.query(), Some("latlng=48.5%2C2.25"));
+        assert!(build_request_url(&base, &GeocodeQuery::default(), None).is_err());
+    }
+
+    #[test]
+    fn dry_run_minimal() {
+        let core = Core::new().expect("Failed to initialize core");
+        let connection = Connection::new(&core.handle());
+        assert_eq!(connection.dry_run(GeocodeQuery::new("Paris")).unwrap().query(), Some("address=Paris"));
+        assert_eq!(connection.dry_run(DegeocodeQuery::from_lat_lng(48.5, 2.25).unwrap()).unwrap().query(), Some("latlng=48.5%2C2.25"));
+        // Unset fields are omitted rather than serialized as null
+        let json = serde_json::to_value(GeocodeQuery::new("Paris")).unwrap();
+        assert_eq!(json.as_object().unwrap().keys().collect::<Vec<_>>(), vec!["address"]);
+    }
+
+    #[test]
+    fn dry_run_channel() {
+        let core = Core::new().expect("Failed to initialize core");
+        let connection = Connection::new(&core.handle()).channel("checkout");
+        let url = connection.dry_run(GeocodeQuery::new("Paris")).unwrap();
+        assert_eq!(url.query(), Some("address=Paris&channel=checkout"));
+    }
+
+    #[test]
+    fn coordinates_distance() {
+        let ground = Coordinates::validated(COORDINATES.0, COORDINATES.1).unwrap();
+        let raised = Coordinates::from(WGS84::new(COORDINATES.0, COORDINATES.1, 100.0));
+        assert!(ground.distance_2d(&raised) < 1e-6);
+        assert!((ground.distance_3d(&raised) - 100.0).abs() < 1e-3);
+
+        let paris = Coordinates::validated(48.8566, 2.3522).unwrap();
+        let london = Coordinates::validated(51.5074, -0.1278).unwrap();
+        let (surface, chord) = (paris.distance_2d(&london), paris.distance_3d(&london));
+        assert!((surface - 344_000.0).abs() < 2_000.0, "{}", surface);
+        assert!(chord < surface);
+    }
+
+    #[test]
+    fn coordinates_bearings() {
+        let at = |lat, lng| Coordinates::validated(lat, lng).unwrap();
+        let close = |a: f64, b: f64| (a - b).abs() < 0.01;
+        assert!(close(at(0.0, 0.0).initial_bearing_to(&at(10.0, 0.0)), 0.0));
+        assert!(close(at(0.0, 0.0).initial_bearing_to(&at(0.0, 90.0)), 90.0));
+        assert!(close(at(0.0, 0.0).initial_bearing_to(&at(-10.0, 0.0)), 180.0));
+        assert!(close(at(0.0, 0.0).initial_bearing_to(&at(0.0, -90.0)), 270.0));
+        // Baghdad to Osaka, which curves north of the parallel they share
+        let (baghdad, osaka) = (at(35.0, 45.0), at(35.0, 135.0));
+        assert!(close(baghdad.initial_bearing_to(&osaka), 60.16), "{}", baghdad.initial_bearing_to(&osaka));
+        assert!(close(baghdad.final_bearing_to(&osaka), 119.84), "{}", baghdad.final_bearing_to(&osaka));
+    }
+
+    #[test]
+    fn coordinates_from_maps_url() {
+        let parse = |url| Coordinates::from_maps_url(url).unwrap().to_precision(Coordinates::QUERY_PRECISION);
+        assert_eq!(parse("https://maps.google.com/?q=37.42,-122.08"), "37.42,-122.08");
+        assert_eq!(parse("https://www.google.com/maps/search/?api=1&query=37.42%2C-122.08"), "37.42,-122.08");
+        assert_eq!(parse("https://www.google.com/maps/@37.42,-122.08,15z"), "37.42,-122.08");
+        assert_eq!(parse("https://www.google.com/maps/place/Googleplex/@37.4,-122.1,17z/data=!3m1!4b1!4m5!3m4!1s0x0:0x0!8m2!3d37.42241!4d-122.08561"),
+            "37.42241,-122.08561");
+        assert!(Coordinates::from_maps_url("https://maps.google.com/?q=Googleplex").is_err());
+        assert!(Coordinates::from_maps_url("not a url").is_err());
+    }
+
+    #[test]
+    fn coordinates_from_str() {
+        let coordinates: Coordinates = "37.42241,-122.08561".parse().unwrap();
+        assert_eq!(coordinates.latitude_degrees(), COORDINATES.0);
+        assert_eq!(coordinates.longitude_degrees(), COORDINATES.1);
+        let spaced: Coordinates = " 37.42241 , -122.08561 ".parse().unwrap();
+        assert_eq!(spaced.to_string(), coordinates.to_string());
+        let reparsed: Coordinates = coordinates.to_string().parse().unwrap();
+        assert!((reparsed.latitude_degrees() - COORDINATES.0).abs() < 1e-9);
+        assert!((reparsed.longitude_degrees() - COORDINATES.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn coordinates_from_str_malformed() {
+        assert!("37.42241".parse::<Coordinates>().is_err());
+        assert!("37.42241,-122.08561,0".parse::<Coordinates>().is_err());
+        assert!("north,west".parse::<Coordinates>().is_err());
+        assert!("91,0".parse::<Coordinates>().is_err());
+        assert!("NaN,0".parse::<Coordinates>().is_err());
+    }
+
+    fn component_name(long_name: &str, short_name: &str) -> Option<ComponentName> {
+        Some(ComponentName { long_name: long_name.to_string(), short_name: short_name.to_string() })
+    }
+
+    #[test]
+    fn structured_address() {
+        let reply = serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results.remove(0);
+        assert_eq!(reply.component(Type::Route).map(AddressComponent::short_name), Some("Amphitheatre Pkwy"));
+        assert_eq!(reply.to_structured_address(), StructuredAddress {
+            street_number: component_name("1600", "1600"),
+            route: component_name("Amphitheatre Parkway", "Amphitheatre Pkwy"),
+            locality: component_name("Mountain View", "Mountain View"),
+            administrative_area_level_2: component_name("Santa Clara County", "Santa Clara County"),
+            administrative_area_level_1: component_name("California", "CA"),
+            postal_code: component_name("94043", "94043"),
+            country: component_name("United States", "US"),
+            ..StructuredAddress::default()
+        });
+    }
+
+    #[test]
+    fn structured_address_international() {
+        let reply = serde_json::from_str::<ReplyResult>(GEOCODE_PARIS_FIXTURE).unwrap().results.remove(0);
+        let address = reply.to_structured_address();
+        assert_eq!(address.route, component_name("Avenue Anatole France", "Av. Anatole France"));
+        assert_eq!(address.locality, component_name("Paris", "Paris"));
+        assert_eq!(address.administrative_area_level_1, component_name("Île-de-France", "IDF"));
+        assert_eq!(address.country, component_name("France", "FR"));
+        assert_eq!(address.neighborhood, None);
+    }
+
+    #[test]
+    fn missing_viewport() {
+        let reply = serde_json::from_str::<ReplyResult>(GEOCODE_NO_VIEWPORT_FIXTURE).unwrap().results.remove(0);
+        let geometry = reply.geometry;
+        assert_eq!(geometry.viewport.northeast.to_string(), geometry.location.to_string());
+        assert_eq!(geometry.viewport.southwest.to_string(), geometry.location.to_string());
+        assert!(geometry.bounds.is_none());
+    }
+
+    #[test]
+    fn intersection() {
+        match Place::intersection(" Hollywood Blvd ", "Vine St\n") {
+            Some(Place::Address { address }) => assert_eq!(address, "Hollywood Blvd & Vine St"),
+            p => panic!("Unexpected place {:?}", p),
+        }
+        assert!(Place::intersection("Hollywood Blvd", " ").is_none());
+
+        let reply = serde_json::from_str::<ReplyResult>(GEOCODE_INTERSECTION_FIXTURE).unwrap().results.remove(0);
+        assert!(reply.is_intersection());
+        assert_eq!(reply.intersection_routes(), Some(("Hollywood Boulevard".to_string(), "Vine Street".to_string())));
+
+        let reply = serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results.remove(0);
+        assert!(!reply.is_intersection());
+        assert_eq!(reply.intersection_routes(), None);
+    }
+
+    #[test]
+    fn viewport_geojson() {
+        let viewport = Viewport {
+            northeast: WGS84::new(49.5, 3.5, 0.0).into(),
+            southwest: WGS84::new(48.5, 2.25, 0.0).into(),
+        };
+        assert_eq!(viewport.to_geojson_bbox(), [2.25, 48.5, 3.5, 49.5]);
+        assert_eq!(viewport.to_geojson_polygon(), json!({
+            "type": "Polygon",
+            "coordinates": [[[2.25, 48.5], [3.5, 48.5], [3.5, 49.5], [2.25, 49.5], [2.25, 48.5]]],
+        }));
+    }
+
+    #[test]
+    fn coordinates_geojson() {
+        let coordinates: Coordinates = WGS84::new(48.5, 2.25, 0.0).into();
+        assert_eq!(coordinates.to_geojson_point(), json!({ "type": "Point", "coordinates": [2.25, 48.5] }));
+    }
+
+    #[test]
+    fn reply_list() {
+        let mut replies = serde_json::from_str::<ReplyResult>(DEGEOCODE_FIXTURE).unwrap().results;
+        assert_eq!(replies.best_match().unwrap().formatted_address.0, ADDRESS);
+        assert_eq!(replies.most_precise().unwrap().formatted_address.0, ADDRESS);
+        replies.reverse();
+        assert_eq!(replies.best_match().unwrap().formatted_address.0, "Mountain View, CA, USA");
+        assert_eq!(replies.most_precise().unwrap().formatted_address.0, ADDRESS);
+        assert!(Vec::<Reply>::new().best_match().is_none());
+
+        let mut tied = serde_json::from_str::<ReplyResult>(GEOCODE_PARIS_FIXTURE).unwrap().results;
+        tied.append(&mut replies);
+        assert_eq!(tied.most_precise().unwrap().formatted_address.0, "5 Av. Anatole France, 75007 Paris, France");
+    }
+
+    #[test]
+    fn coordinates_string_encoded() {
+        let numeric: Coordinates = serde_json::from_str(r#"{ "lat": 37.42241, "lng": -122.08561 }"#).unwrap();
+        let string: Coordinates = serde_json::from_str(r#"{ "lat": "37.42241", "lng": "-122.08561" }"#).unwrap();
+        let integer: Coordinates = serde_json::from_str(r#"{ "lat": 37, "lng": -122 }"#).unwrap();
+        assert_eq!(numeric.to_string(), string.to_string());
+        assert_eq!(integer.latitude_degrees().round(), 37.0);
+        assert!(serde_json::from_str::<Coordinates>(r#"{ "lat": "north", "lng": "-122.08561" }"#).is_err());
+    }
+
+    #[test]
+    fn fixture_cached() {
+        let (mut core, connection) = fixture_start(GEOCODE_FIXTURE);
+        let connection = CachedConnection::new(connection, 10, Duration::from_secs(60));
+        // Built before the first request completes, but only looks up the cache once polled
+        let pending = connection.geocode(ADDRESS);
+        let first = core.run(connection.geocode(ADDRESS)).unwrap();
+        // The fixture server only answers once, so these must come from the cache
+        let second = core.run(connection.geocode(ADDRESS)).unwrap();
+        let third = core.run(pending).unwrap();
+        assert_eq!(first[0].formatted_address.0, second[0].formatted_address.0);
+        assert_eq!(first[0].formatted_address.0, third[0].formatted_address.0);
+        assert_eq!(connection.len(), 1);
+    }
+
+    #[test]
+    fn insecure_base_url() {
+        let core = Core::new().expect("Failed to initialize core");
+        let insecure = Url::parse("http://maps.google.com/maps/api/geocode/json").unwrap();
+        assert!(Connection::with_base_url(&core.handle(), insecure).is_err());
+        let secure = Url::parse("https://proxy.example.com/geocode/json").unwrap();
+        assert!(Connection::with_base_url(&core.handle(), secure).is_ok());
+    }
+
+    #[test]
+    fn base_url_str() {
+        let core = Core::new().expect("Failed to initialize core");
+        let error = Connection::with_base_url_str(&core.handle(), "https://[proxy/json").err().unwrap();
+        assert!(error.to_string().starts_with("Invalid base URL \"https://[proxy/json\""), "{}", error);
+        assert!(Connection::with_base_url_str(&core.handle(), "https://proxy.example.com/json?key=abc").is_err());
+        let connection = Connection::with_base_url_str(&core.handle(), "https://proxy.example.com/geocode/json").unwrap();
+        assert_eq!(connection.dry_run(GeocodeQuery::new("Paris")).unwrap().as_str(), "https://proxy.example.com/geocode/json?address=Paris");
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn geocode_query_default() {
+        assert_eq!(serde_urlencoded::to_string(GeocodeQuery::default()).unwrap(), "");
+        let query = GeocodeQuery::default().language(Language::French).address("Paris");
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(), "address=Paris&language=fr");
+    }
+
+    #[test]
+    fn geocode_query_session_token() {
+        let query = GeocodeQuery::new("Paris").session_token("abc123");
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(), "address=Paris&sessiontoken=abc123");
+    }
+
+    #[test]
+    fn reply_json_round_trip() {
+        let replies = serde_json::from_str::<ReplyResult>(DEGEOCODE_FIXTURE).unwrap().results;
+        let json = to_reply_json(&replies, &StatusCode::Ok).unwrap();
+        let parsed = serde_json::from_str::<ReplyResult>(&json).unwrap().into_results().unwrap();
+        assert_eq!(parsed.len(), replies.len());
+        for (a, b) in parsed.iter().zip(&replies) {
+            assert_eq!(a.formatted_address.to_string(), b.formatted_address.to_string());
+            assert_eq!(a.place_id, b.place_id);
+            assert_eq!(a.geometry.location.to_string(), b.geometry.location.to_string());
+            assert_eq!(a.geometry.bounds.is_some(), b.geometry.bounds.is_some());
+        }
+        let json = to_reply_json(&[], &StatusCode::ZeroResults).unwrap();
+        assert_eq!(json, r#"{"results":[],"status":"ZERO_RESULTS"}"#);
+    }
+
+    #[test]
+    fn connection_last_response() {
+        let response = ok_response(GEOCODE_FIXTURE).replacen("\r\n", "\r\nX-Quota-Remaining: 41\r\n", 1);
+        let (mut core, connection) = fixture_connection(vec![response]);
+        assert!(connection.last_response().is_none());
+        core.run(connection.geocode(ADDRESS)).unwrap();
+        let info = connection.last_response().unwrap();
+        assert_eq!(info.status, reqwest::StatusCode::Ok);
+        assert_eq!(info.headers.get_raw("X-Quota-Remaining").unwrap().one(), Some(&b"41"[..]));
+    }
+
+    #[test]
+    fn filter_type() {
+        let replies = serde_json::from_str::<ReplyResult>(DEGEOCODE_FIXTURE).unwrap().results;
+        let localities = replies.iter().filter_type(Type::Locality).collect::<Vec<_>>();
+        assert_eq!(localities.len(), 1);
+        assert!(localities[0].types.contains(&Type::Locality));
+        assert_eq!(replies.iter().filter_type(Type::Political).count(), 1);
+        assert_eq!(replies.into_iter().filter_type(Type::TransitStation).count(), 0);
+    }
+
+    #[test]
+    fn coordinates_to_precision() {
+        let coordinates = Coordinates::validated(37.422_408_8, -122.085_608_6).unwrap();
+        assert_eq!(coordinates.to_precision(7), "37.4224088,-122.0856086");
+        assert_eq!(coordinates.to_precision(2), "37.42,-122.09");
+        assert_eq!(coordinates.to_precision(0), "37,-122");
+        assert_eq!(Coordinates::validated(48.5, 2.0).unwrap().to_precision(7), "48.5,2");
+        let query = serde_urlencoded::to_string(DegeocodeQuery::new(coordinates)).unwrap();
+        assert_eq!(query, "latlng=37.4224088%2C-122.0856086");
+    }
+
+    #[test]
+    fn coordinates_from_f32() {
+        let coordinates = Coordinates::from(WGS84::new(48.5f32, 2.25f32, 0f32));
+        assert!((coordinates.latitude_degrees() - 48.5).abs() < 1e-5);
+        assert!((coordinates.longitude_degrees() - 2.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn fixture_geocode_each() {
+        let (mut core, connection) = fixture_start(GEOCODE_FIXTURE);
+        let replies = core.run(connection.geocode_each(ADDRESS).take(1).collect()).unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+    }
+
+    #[test]
+    fn degeocode_query_type_filters() {
+        let query = DegeocodeQuery::new(Coordinates::validated(48.5, 2.25).unwrap())
+            .result_types(vec![Type::StreetAddress, Type::Locality])
+            .location_types(Some(LocationType::Rooftop));
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(), "latlng=48.5%2C2.25&result_type=locality%7Cstreet_address&location_type=ROOFTOP");
+    }
+
+    #[test]
+    fn lat_lng_round_trip() {
+        #[derive(Deserialize, Serialize)]
+        struct Marker {
+            #[serde(with="lat_lng")]
+            position: Coordinates,
+        }
+        let marker = Marker { position: Coordinates::validated(48.5, 2.25).unwrap() };
+        let json = serde_json::to_string(&marker).unwrap();
+        assert_eq!(json, r#"{"position":{"lat":48.5,"lng":2.25}}"#);
+        let parsed: Marker = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.position.to_string(), marker.position.to_string());
+        assert_eq!(serde_json::to_string(&marker.position).unwrap(), r#""48.5,2.25""#);
+    }
+
+    #[test]
+    fn degeocode_query_from_lat_lng() {
+        let query = DegeocodeQuery::from_lat_lng(48.5, 2.25).unwrap();
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(), "latlng=48.5%2C2.25");
+        assert!(DegeocodeQuery::from_lat_lng(91.0, 2.25).is_err());
+        assert!(DegeocodeQuery::from_lat_lng(f64::NAN, 2.25).is_err());
+    }
+
+    #[test]
+    fn best_confident() {
+        let mut fixture: serde_json::Value = serde_json::from_str(DEGEOCODE_FIXTURE).unwrap();
+        let replies = serde_json::from_value::<ReplyResult>(fixture.clone()).unwrap().results;
+        assert!(!replies[0].partial_match);
+        assert_eq!(replies.best_confident().unwrap().place_id, replies[0].place_id);
+
+        fixture["results"][0]["partial_match"] = json!(true);
+        let replies = serde_json::from_value::<ReplyResult>(fixture).unwrap().results;
+        assert!(replies[0].partial_match);
+        assert_eq!(replies[1].geometry.location_type, LocationType::Approximate);
+        assert!(replies.best_confident().is_none());
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn fixture_geocode_configured_query() {
+        let (mut core, connection) = fixture_start(GEOCODE_PARIS_FIXTURE);
+        let query = GeocodeQuery::new("Rue de Rivoli").language(Language::French).region(Region::France);
+        let replies = core.run(connection.geocode(query)).unwrap();
+        assert!(!replies.is_empty());
+    }
+
+    #[test]
+    fn coordinates_to_ecef() {
+        let ecef = Coordinates::validated(0.0, 0.0).unwrap().to_ecef();
+        assert!((ecef.x() - 6_378_137.0).abs() < 1e-3);
+        assert!(ecef.y().abs() < 1e-3);
+        assert!(ecef.z().abs() < 1e-3);
+        let north_pole = Coordinates::validated(90.0, 0.0).unwrap().to_ecef();
+        assert!((north_pole.z() - 6_356_752.314).abs() < 1e-2);
+    }
+
+    #[test]
+    fn viewport_interpolate() {
+        let viewport = Viewport {
+            northeast: Coordinates::validated(10.0, 20.0).unwrap(),
+            southwest: Coordinates::validated(-10.0, -20.0).unwrap(),
+        };
+        let close = |a: Coordinates, b: (f64, f64)| (a.latitude_degrees() - b.0).abs() < 1e-9 && (a.longitude_degrees() - b.1).abs() < 1e-9;
+        assert!(close(viewport.interpolate(0.0), (-10.0, -20.0)));
+        assert!(close(viewport.interpolate(1.0), (10.0, 20.0)));
+        assert!(close(viewport.interpolate(0.5), (0.0, 0.0)));
+        let quarter = viewport.interpolate(0.25);
+        assert!(quarter.latitude_degrees() > -10.0 && quarter.latitude_degrees() < 0.0);
+        assert!(quarter.longitude_degrees() > -20.0 && quarter.longitude_degrees() < 0.0);
+    }
+
+    #[test]
+    fn within_meters() {
+        let reply = &serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results[0];
+        let location = reply.geometry.location;
+        assert!(reply.within_meters(&location, 0.0));
+        // A thousandth of a degree of latitude is about 111 metres
+        let nearby = Coordinates::validated(location.latitude_degrees() + 0.001, location.longitude_degrees()).unwrap();
+        assert!(reply.within_meters(&nearby, 500.0));
+        assert!(!reply.within_meters(&nearby, 100.0));
+    }
+
+    #[test]
+    fn normalize_multi_line_address() {
+        let input = "  1600 Amphitheatre Pkwy\n\tMountain View,  CA 94043\r\n\nUSA ";
+        let expected = "1600 Amphitheatre Pkwy, Mountain View, CA 94043, USA";
+        assert_eq!(normalize_address(input), expected);
+        assert_eq!(normalize_address("Main St,\nSpringfield"), "Main St, Springfield");
+        match Place::from(input) {
+            Place::Address { address } => assert_eq!(address, expected),
+            p => panic!("Unexpected place {:?}", p),
+        }
+        let query = serde_urlencoded::to_string(GeocodeQuery::default().address(input)).unwrap();
+        assert_eq!(query, "address=1600+Amphitheatre+Pkwy%2C+Mountain+View%2C+CA+94043%2C+USA");
+    }
+
+    #[test]
+    fn fixture_geocode_timed() {
+        let (mut core, connection) = fixture_start(GEOCODE_FIXTURE);
+        let timed = core.run(connection.geocode_timed(ADDRESS)).unwrap();
+        assert_eq!(timed.replies[0].formatted_address.0, ADDRESS);
+        assert!(timed.elapsed < Duration::from_secs(10));
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn degeocode_query_parameters() {
+        let query = DegeocodeQuery::from_lat_lng(48.5, 2.25).unwrap()
+            .language(Language::French)
+            .result_types(Some(Type::StreetAddress))
+            .location_types(Some(LocationType::Rooftop));
+        let keys = serde_urlencoded::from_str::<Vec<(String, String)>>(&serde_urlencoded::to_string(query).unwrap()).unwrap()
+            .into_iter()
+            .map(|(k, _)| k)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["latlng", "language", "result_type", "location_type"]);
+    }
+
+    #[test]
+    fn reply_static_map_url() {
+        let reply = &serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results[0];
+        assert_eq!(reply.static_map_url(15, (600, 300)),
+            "https://maps.googleapis.com/maps/api/staticmap?center=37.42241%2C-122.08561&zoom=15&size=600x300&markers=37.42241%2C-122.08561");
+    }
+
+    #[test]
+    fn degeocode_coordinate_precision() {
+        let query = DegeocodeQuery::from_lat_lng(37.422_414_159, -122.085_612_345).unwrap();
+        assert_eq!(serde_urlencoded::to_string(query.clone()).unwrap(), "latlng=37.4224142%2C-122.0856123");
+        assert_eq!(serde_urlencoded::to_string(query.coordinate_precision(3)).unwrap(), "latlng=37.422%2C-122.086");
+    }
+
+    #[test]
+    fn reply_summary() {
+        let reply = &serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results[0];
+        let summary = "1600 Amphitheatre Pkwy, Mountain View, CA 94043, USA at 37.42241,-122.08561 (Rooftop, place ID ChIJ2eUgeAK6j4ARbn5u_wAGqWA)";
+        assert_eq!(reply.summary(), summary);
+        assert_eq!(reply.to_string(), summary);
+    }
+
+    #[test]
+    fn capitalized_results() {
+        let fixture = include_str!("../tests/fixtures/geocode_capitalized_results.json");
+        let replies = serde_json::from_str::<ReplyResult>(fixture).unwrap().into_results().unwrap();
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+    }
+
+    #[test]
+    fn unknown_location_type() {
+        let fixture = include_str!("../tests/fixtures/geocode_unknown_location_type.json");
+        let replies = serde_json::from_str::<ReplyResult>(fixture).unwrap().results;
+        assert_eq!(replies[0].geometry.location_type, LocationType::Unknown);
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+    }
+
+    #[test]
+    fn centroid() {
+        let mut fixture: serde_json::Value = serde_json::from_str(DEGEOCODE_FIXTURE).unwrap();
+        let set_location = |fixture: &mut serde_json::Value, i: usize, lat: f64, lng: f64| {
+            fixture["results"][i]["geometry"]["location"] = json!({ "lat": lat, "lng": lng });
+        };
+        set_location(&mut fixture, 0, 10.0, 179.0);
+        set_location(&mut fixture, 1, 10.0, -179.0);
+        let replies = serde_json::from_value::<ReplyResult>(fixture.clone()).unwrap().results;
+        let centroid = replies.centroid().unwrap();
+        // Naively averaging longitudes would give 0 rather than the antimeridian
+        assert!((centroid.longitude_degrees().abs() - 180.0).abs() < 1e-6);
+        assert!((centroid.latitude_degrees() - 10.0).abs() < 1e-2);
+
+        set_location(&mut fixture, 0, 0.0, 0.0);
+        set_location(&mut fixture, 1, 0.0, 180.0);
+        let replies = serde_json::from_value::<ReplyResult>(fixture).unwrap().results;
+        assert!(replies.centroid().is_none());
+        assert!(replies[..0].centroid().is_none());
+        assert_eq!(replies[..1].centroid().unwrap().to_precision(6), "0,0");
+    }
+
+    #[test]
+    fn coordinates_bounds() {
+        for &(latitude, longitude) in &[(90.0, 180.0), (-90.0, -180.0), (89.999999, 179.999999)] {
+            let coordinates = Coordinates::validated(latitude, longitude).unwrap();
+            let reparsed: Coordinates = coordinates.to_string().parse().unwrap();
+            assert!((reparsed.latitude_degrees() - latitude).abs() < 1e-9);
+        }
+        assert!(Coordinates::validated(90.0 + 1e-12, 0.0).is_err());
+        assert!(Coordinates::validated(0.0, 180.0 + 1e-12).is_err());
+
+        let lenient = Coordinates::lenient(95.0, 190.0).unwrap();
+        assert!((lenient.latitude_degrees() - 90.0).abs() < 1e-9);
+        assert!((lenient.longitude_degrees() + 170.0).abs() < 1e-9);
+        assert!((Coordinates::lenient(0.0, -540.0).unwrap().longitude_degrees() + 180.0).abs() < 1e-9);
+        assert!(Coordinates::lenient(f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn degeocode_query_try_from_str() {
+        let query = DegeocodeQuery::try_from("48.5, 2.25").unwrap();
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(), "latlng=48.5%2C2.25");
+        assert!(DegeocodeQuery::try_from("Paris").is_err());
+        assert!(DegeocodeQuery::try_from("91,0").is_err());
+    }
+
+    #[test]
+    fn component_filter_builder() {
+        let filter = ComponentFilter::new().country("US").postal_code("94043").administrative_area("CA");
+        assert_eq!(serde_urlencoded::to_string(GeocodeQuery::new(filter.clone())).unwrap(),
+            "components=administrative_area%3ACA%7Ccountry%3AUS%7Cpostal_code%3A94043");
+        let query = GeocodeQuery::new("Amphitheatre Pkwy").components(filter);
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(),
+            "address=Amphitheatre+Pkwy&components=administrative_area%3ACA%7Ccountry%3AUS%7Cpostal_code%3A94043");
+    }
+
+    #[test]
+    fn component_validation() {
+        assert!(ComponentFilterRule::country("FR").is_ok());
+        assert!(ComponentFilterRule::country("Côte d'Ivoire").is_ok());
+        assert!(ComponentFilterRule::postal_code("94043").is_ok());
+        let reason = |r: std::result::Result<ComponentFilterRule, InvalidComponent>| r.unwrap_err().reason;
+        assert_eq!(reason(ComponentFilterRule::country("F1")), InvalidComponentReason::InvalidCountry);
+        assert_eq!(reason(ComponentFilterRule::country("F")), InvalidComponentReason::InvalidCountry);
+        assert_eq!(reason(ComponentFilterRule::locality(" ")), InvalidComponentReason::Empty);
+        assert_eq!(reason(ComponentFilterRule::route("Main St|Elm St")), InvalidComponentReason::ContainsSeparator);
+        let error = ComponentFilterRule::country("123").unwrap_err();
+        assert_eq!(error.component, "country");
+        assert_eq!(error.to_string(), "Invalid country component \"123\": is not a two letter ISO 3166-1 code or a country name");
+        assert!(ComponentFilter::new().country("US").postal_code("94043").try_build().is_ok());
+        let error = ComponentFilter::new().country("US").postal_code("").try_build().unwrap_err();
+        assert_eq!(error.downcast_ref::<InvalidComponent>().unwrap().component, "postal_code");
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn query_serialization() {
+        assert_eq!(serde_urlencoded::to_string(GeocodeQuery::new("Rue de Rivoli")).unwrap(), "address=Rue+de+Rivoli");
+        assert_eq!(serde_urlencoded::to_string(GeocodeQuery::new(ComponentFilter::new().country("FR").postal_code("75001"))).unwrap(),
+            "components=country%3AFR%7Cpostal_code%3A75001");
+        let bounds = Viewport {
+            northeast: Coordinates::validated(49.5, 3.5).unwrap(),
+            southwest: Coordinates::validated(48.5, 2.25).unwrap(),
+        };
+        let query = GeocodeQuery::new("Rue de Rivoli").language(Language::French).region(Region::France).bounds(bounds);
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(),
+            "address=Rue+de+Rivoli&bounds=48.5%2C2.25%7C49.5%2C3.5&language=fr&region=fr");
+        let query = DegeocodeQuery::from_lat_lng(37.42241, -122.08561).unwrap()
+            .language(Language::Japanese)
+            .result_types(vec![Type::StreetAddress, Type::PostalCode])
+            .location_types(vec![LocationType::Rooftop, LocationType::RangeInterpolated]);
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(),
+            "latlng=37.42241%2C-122.08561&language=ja&result_type=postal_code%7Cstreet_address&location_type=RANGE_INTERPOLATED%7CROOFTOP");
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn query_templates() {
+        let template = GeocodeQuery::new("Rue de Rivoli").region(Region::France);
+        let french = template.clone().language(Language::French);
+        let english = template.clone().language(Language::English);
+        assert_eq!(serde_urlencoded::to_string(&template).unwrap(), "address=Rue+de+Rivoli&region=fr");
+        assert_eq!(serde_urlencoded::to_string(&french).unwrap(), "address=Rue+de+Rivoli&language=fr&region=fr");
+        assert_eq!(serde_urlencoded::to_string(&english).unwrap(), "address=Rue+de+Rivoli&language=en&region=fr");
+
+        let template = DegeocodeQuery::from_lat_lng(48.5, 2.25).unwrap().result_types(Some(Type::StreetAddress));
+        let japanese = template.clone().language(Language::Japanese);
+        assert_eq!(serde_urlencoded::to_string(&template).unwrap(), "latlng=48.5%2C2.25&result_type=street_address");
+        assert_eq!(serde_urlencoded::to_string(&japanese).unwrap(), "latlng=48.5%2C2.25&language=ja&result_type=street_address");
+    }
+
+    #[test]
+    fn place_display() {
+        assert_eq!(Place::from("Rue de Rivoli").to_string(), "Rue de Rivoli");
+        assert_eq!(Place::from(ComponentFilter::new().postal_code("75001").country("FR")).to_string(),
+            "components=country:FR|postal_code:75001");
+    }
+
+    #[test]
+    fn query_normalize_diacritics() {
+        let query = GeocodeQuery::new("Bahnhofstraße 1, Zürich").normalize_diacritics(true);
+        assert_eq!(query.address.as_deref(), Some("Bahnhofstraße 1, Zurich"));
+        let query = query.address("Crème Brûlée Café, Montréal");
+        assert_eq!(query.address.as_deref(), Some("Creme Brulee Cafe, Montreal"));
+        assert_eq!(GeocodeQuery::new("Zürich").address.as_deref(), Some("Zürich"));
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn query_extra_params() {
+        let query = GeocodeQuery::new("Paris").language(Language::French).extra_param("new_feature", "on").extra_param("beta", "1");
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(), "address=Paris&language=fr&beta=1&new_feature=on");
+        let query = DegeocodeQuery::from_lat_lng(48.5, 2.25).unwrap().extra_param("new_feature", "a|b");
+        assert_eq!(serde_urlencoded::to_string(query).unwrap(), "latlng=48.5%2C2.25&new_feature=a%7Cb");
+    }
+
+    #[test]
+    #[cfg(feature="full-enums")]
+    fn query_without_region() {
+        let query = GeocodeQuery::new("Rue de Rivoli").language(Language::French);
+        let parameters = serde_urlencoded::from_str::<Vec<(String, String)>>(&serde_urlencoded::to_string(query).unwrap()).unwrap();
+        assert!(parameters.iter().all(|(k, _)| k != "region"));
+        assert_eq!(parameters.len(), 2);
+    }
+
+    #[test]
+    fn query_validation() {
+        let core = Core::new().expect("Failed to initialize core");
+        let connection = Connection::new(&core.handle());
+        let invalid = |query: GeocodeQuery| connection.dry_run(query).unwrap_err().downcast::<InvalidQuery>().unwrap();
+        assert_eq!(invalid(GeocodeQuery::default()), InvalidQuery::MissingAddressAndComponents);
+        assert_eq!(invalid(GeocodeQuery::new(" \n ")), InvalidQuery::EmptyAddress);
+        assert_eq!(invalid(GeocodeQuery::new(ComponentFilter::new())), InvalidQuery::NoComponentFilters);
+        match invalid(GeocodeQuery::new(ComponentFilter::new().country("F1"))) {
+            InvalidQuery::InvalidComponent(e) => assert_eq!(e.reason, InvalidComponentReason::InvalidCountry),
+            e => panic!("Unexpected error {:?}", e),
+        }
+        assert!(connection.dry_run(GeocodeQuery::new(ComponentFilter::new().country("FR"))).is_ok());
+    }
+
+    #[test]
+    fn fixture_geocode_replies() {
+        let mut fixture: serde_json::Value = serde_json::from_str(GEOCODE_FIXTURE).unwrap();
+        fixture["error_message"] = "Results may be incomplete".into();
+        let (mut core, connection) = fixture_connection(vec![
+            ok_response(&fixture.to_string()),
+            ok_response(r#"{ "error_message": "Nothing here", "results": [], "status": "ZERO_RESULTS" }"#),
+        ]);
+        let replies = core.run(connection.geocode_replies(ADDRESS)).unwrap();
+        assert!(!replies.is_zero_results());
+        assert_eq!(replies.message(), Some("Results may be incomplete"));
+        assert_eq!(replies.results().len(), 1);
+        for reply in &replies {
+            assert_eq!(reply.formatted_address.0, ADDRESS);
+        }
+        assert_eq!(replies.into_iter().count(), 1);
+        let replies = core.run(connection.geocode_replies("Nowhere")).unwrap();
+        assert!(replies.is_zero_results());
+        assert_eq!(replies.message(), Some("Nothing here"));
+        assert_eq!(replies.into_iter().count(), 0);
+    }
+
+    #[test]
+    fn fixture_observer() {
+        struct Recorder(Rc<RefCell<Vec<String>>>);
+        impl Observer for Recorder {
+            fn on_request(&self, url: &Url) {
+                self.0.borrow_mut().push(format!("request {}", url.query().unwrap()));
+            }
+            fn on_response(&self, status: &StatusCode, _elapsed: Duration) {
+                self.0.borrow_mut().push(format!("response {:?}", status));
+            }
+            fn on_error(&self, _error: &Error) {
+                self.0.borrow_mut().push("error".to_string());
+            }
+        }
+
+        let (mut core, connection) = fixture_connection(vec![ok_response(GEOCODE_FIXTURE), ok_response("not json")]);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let connection = connection.observer(Recorder(events.clone()));
+        core.run(connection.geocode("Paris")).unwrap();
+        assert!(core.run(connection.geocode("Paris")).is_err());
+        assert_eq!(*events.borrow(), vec!["request address=Paris", "response Ok", "request address=Paris", "error"]);
+    }
+
+    #[test]
+    fn fixture_geocode_labeled() {
+        let (mut core, connection) = fixture_connection(vec![ok_response(GEOCODE_FIXTURE), ok_response(r#"{ "results": [], "status": "ZERO_RESULTS" }"#)]);
+        let results = core.run(connection.geocode_labeled(vec![(7, ADDRESS), (9, "Nowhere")], 1)).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results.iter().map(|&(key, _)| key).collect::<Vec<_>>(), vec![7, 9]);
+        assert_eq!(results.iter().filter(|(_, result)| result.is_ok()).count(), 1);
+    }
+
+    #[test]
+    fn fixture_degeocode_filters() {
+        let (mut core, connection) = fixture_start(DEGEOCODE_FIXTURE);
+        let all = serde_json::from_str::<ReplyResult>(DEGEOCODE_FIXTURE).unwrap().results;
+        let query = DegeocodeQuery::from_lat_lng(COORDINATES.0, COORDINATES.1).unwrap()
+            .result_types(Some(Type::StreetAddress))
+            .location_types(Some(LocationType::Rooftop));
+        let replies = core.run(connection.degeocode(query)).unwrap();
+        assert!(!replies.is_empty() && replies.len() < all.len());
+        assert!(replies.iter().all(|r| r.types.contains(&Type::StreetAddress) && r.geometry.location_type == LocationType::Rooftop));
+    }
+
+    #[test]
+    fn fixture_degeocode_ocean() {
+        let (mut core, connection) = fixture_connection(vec![ok_response(r#"{ "results": [], "status": "ZERO_RESULTS" }"#)]);
+        let pacific = DegeocodeQuery::from_lat_lng(0.0, -140.0).unwrap();
+        assert!(core.run(connection.degeocode(pacific)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn fixture_roundtrip() {
+        let (mut core, connection) = fixture_connection(vec![ok_response(GEOCODE_FIXTURE), ok_response(DEGEOCODE_FIXTURE)]);
+        let (coordinates, address) = core.run(connection.roundtrip("1600 amphitheatre pkwy mountain view")).unwrap();
+        assert_eq!(coordinates.to_string(), "37.42241,-122.08561");
+        assert_eq!(address.0, ADDRESS);
+    }
+
+    #[test]
+    fn fixture_geocode_within_place() {
+        let (mut core, connection) = fixture_connection(vec![ok_response(GEOCODE_PARIS_FIXTURE), ok_response(GEOCODE_FIXTURE)]);
+        let replies = core.run(connection.geocode_within_place("ChIJD7fiBh9u5kcRYJSMaMOCCwQ", "Apt 2, 1600 Amphitheatre Pkwy")).unwrap();
+        assert_eq!(replies[0].formatted_address.0, ADDRESS);
+        assert_eq!(connection.dry_run(PlaceIdQuery { place_id: "ChIJD7fiBh9u5kcRYJSMaMOCCwQ".into() }).unwrap().query(),
+            Some("place_id=ChIJD7fiBh9u5kcRYJSMaMOCCwQ"));
+    }
+
+    #[test]
+    fn reply_administrative_areas() {
+        let reply = &serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results[0];
+        let areas = reply.administrative_areas().into_iter()
+            .map(|(level, c)| (level, c.short_name()))
+            .collect::<Vec<_>>();
+        assert_eq!(areas, vec![(1, "CA"), (2, "Santa Clara County")]);
+    }
+
+    #[test]
+    fn reply_granularity() {
+        let replies = serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results;
+        assert_eq!(replies[0].granularity(), Granularity::StreetAddress);
+        assert!(replies[0].granularity() >= Granularity::Route);
+        let fixture = include_str!("../tests/fixtures/geocode_sublocality.json");
+        let replies = serde_json::from_str::<ReplyResult>(fixture).unwrap().results;
+        assert_eq!(replies[0].granularity(), Granularity::Premise);
+        assert!(Granularity::Country < Granularity::AdministrativeArea);
+    }
+
+    #[test]
+    fn reply_confidence() {
+        let mut reply = serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results.remove(0);
+        assert!((reply.confidence() - 1.0).abs() < 1e-6);
+        reply.partial_match = true;
+        reply.geometry.location_type = LocationType::Approximate;
+        reply.types = vec![Type::Locality, Type::Political];
+        assert!((reply.confidence() - 0.15).abs() < 1e-6);
+    }
+
+    #[test]
+    fn reply_sublocality() {
+        let fixture = include_str!("../tests/fixtures/geocode_sublocality.json");
+        let reply = &serde_json::from_str::<ReplyResult>(fixture).unwrap().results[0];
+        assert_eq!(reply.sublocality().unwrap().long_name(), "4 Chome");
+        assert!(reply.neighborhood().is_none());
+        let reply = &serde_json::from_str::<ReplyResult>(GEOCODE_FIXTURE).unwrap().results[0];
+        assert!(reply.sublocality().is_none());
+    }
+
+    #[test]
+    fn viewport_from_corners() {
+        let a = Coordinates::validated(48.9, 2.2).unwrap();
+        let b = Coordinates::validated(48.8, 2.5).unwrap();
+        assert_eq!(Viewport::from_corners(a, b).to_geojson_bbox(), [2.2, 48.8, 2.5, 48.9]);
+        assert_eq!(Viewport::from_corners(b, a).to_geojson_bbox(), [2.2, 48.8, 2.5, 48.9]);
+    }
+
+    #[test]
+    fn viewport_from_pair() {
+        let a = Coordinates::validated(48.9, 2.2).unwrap();
+        let b = Coordinates::validated(48.8, 2.5).unwrap();
+        let viewport = Viewport::from((a, b));
+        assert_eq!(viewport.to_geojson_bbox(), [2.2, 48.8, 2.5, 48.9]);
+        let (southwest, northeast) = viewport.into();
+        assert_eq!((southwest.to_string(), northeast.to_string()), ("48.8,2.2".to_string(), "48.9,2.5".to_string()));
+    }
+
+    #[test]
+    fn viewport_around() {
+        let center = Coordinates::validated(0.0, 179.995).unwrap();
+        let viewport = Viewport::around(center, 1000.0).unwrap();
+        let [west, south, east, north] = viewport.to_geojson_bbox();
+        assert!((north - 0.009).abs() < 1e-3 && (south + 0.009).abs() < 1e-3);
+        assert!(west > east, "expected the viewport to cross the antimeridian");
+        assert!((viewport.northeast.distance(&center) - 1000.0 * 2f64.sqrt()).abs() < 1.0);
+        let polar = Viewport::around(Coordinates::validated(89.99, 10.0).unwrap(), 10_000.0).unwrap();
+        assert_eq!(polar.to_geojson_bbox()[3], 90.0);
+        assert_eq!(polar.to_geojson_bbox()[0], -180.0);
+        assert!(Viewport::around(center, f64::NAN).is_err());
+        assert!(Viewport::around(center, f64::INFINITY).is_err());
+        assert!(Viewport::around(center, -1.0).is_err());
+    }
+
+    #[test]
+    fn geometry_effective_bounds() {
+        let replies = serde_json::from_str::<ReplyResult>(DEGEOCODE_FIXTURE).unwrap().results;
+        let without = &replies[0].geometry;
+        assert!(without.bounds.is_none());
+        assert_eq!(without.effective_bounds().northeast.to_string(), without.viewport.northeast.to_string());
+        let with = &replies[1].geometry;
+        let bounds = with.bounds.unwrap();
+        assert_eq!(with.effective_bounds().northeast.to_string(), bounds.northeast.to_string());
+        assert_eq!(with.effective_bounds().southwest.to_string(), bounds.southwest.to_string());
     }
 
     #[test]
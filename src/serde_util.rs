@@ -1,79 +1,34 @@
-use serde::ser::{self, Serialize, Serializer, SerializeStructVariant, SerializeTupleVariant, Impossible};
+use serde::de::{self, Deserializer};
+use serde::Serialize;
+use serde_json::{self, Value};
 use std;
 
-// Many thanks to dtolnay
-pub fn variant_name<T: Serialize>(t: &T) -> &'static str {
-    #[derive(Debug)]
-    struct NotEnum;
-    type Result<T> = std::result::Result<T, NotEnum>;
-    impl std::error::Error for NotEnum {
-        fn description(&self) -> &str { "not struct" }
-    }
-    impl std::fmt::Display for NotEnum {
-        fn fmt(&self, _f: &mut std::fmt::Formatter) -> std::fmt::Result { unimplemented!() }
-    }
-    impl ser::Error for NotEnum {
-        fn custom<T: std::fmt::Display>(_msg: T) -> Self { NotEnum }
-    }
-
-    struct VariantName;
-    impl Serializer for VariantName {
-        type Ok = &'static str;
-        type Error = NotEnum;
-        type SerializeSeq = Impossible<Self::Ok, Self::Error>;
-        type SerializeTuple = Impossible<Self::Ok, Self::Error>;
-        type SerializeTupleStruct = Impossible<Self::Ok, Self::Error>;
-        type SerializeTupleVariant = Enum;
-        type SerializeMap = Impossible<Self::Ok, Self::Error>;
-        type SerializeStruct = Impossible<Self::Ok, Self::Error>;
-        type SerializeStructVariant = Enum;
-        fn serialize_bool(self, _v: bool) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_i8(self, _v: i8) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_i16(self, _v: i16) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_i32(self, _v: i32) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_i64(self, _v: i64) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_u8(self, _v: u8) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_u16(self, _v: u16) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_u32(self, _v: u32) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_u64(self, _v: u64) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_f32(self, _v: f32) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_f64(self, _v: f64) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_char(self, _v: char) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_str(self, _v: &str) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_bytes(self, _v: &[u8]) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_none(self) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_some<T: ?Sized + Serialize>(self, _value: &T) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_unit(self) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_unit_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str) -> Result<Self::Ok> { Ok(variant) }
-        fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, _value: &T) -> Result<Self::Ok> { Err(NotEnum) }
-        fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _name: &'static str, _variant_index: u32, variant: &'static str, _value: &T) -> Result<Self::Ok> { Ok(variant) }
-        fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> { Err(NotEnum) }
-        fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> { Err(NotEnum) }
-        fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeTupleStruct> { Err(NotEnum) }
-        fn serialize_tuple_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, _len: usize) -> Result<Self::SerializeTupleVariant> { Ok(Enum(variant)) }
-        fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> { Err(NotEnum) }
-        fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> { Err(NotEnum) }
-        fn serialize_struct_variant(self, _name: &'static str, _variant_index: u32, variant: &'static str, _len: usize) -> Result<Self::SerializeStructVariant> { Ok(Enum(variant)) }
+/// Serializes a value to the bare string it would appear as in a query parameter
+///
+/// Unit variants become their (renamed) variant name, and anything that
+/// serializes as a string is passed through unchanged.
+pub fn to_plain_string<T: Serialize>(t: &T) -> std::result::Result<String, String> {
+    match serde_json::to_value(t) {
+        Ok(Value::String(s)) => Ok(s),
+        Ok(v) => Err(format!("Expected a string-like value, got {}", v)),
+        Err(e) => Err(e.to_string()),
     }
+}
 
-    struct Enum(&'static str);
-    impl SerializeStructVariant for Enum {
-        type Ok = &'static str;
-        type Error = NotEnum;
-        fn serialize_field<T: ?Sized + Serialize>(&mut self, _key: &'static str, _value: &T) -> Result<()> { Ok(()) }
-        fn end(self) -> Result<Self::Ok> {
-            Ok(self.0)
+/// Deserializes an f64 that may also be encoded as a string, eg `"37.42241"`
+pub fn f64_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<f64, D::Error> {
+    struct Visitor;
+    impl<'de> de::Visitor<'de> for Visitor {
+        type Value = f64;
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a number or a string containing a number")
         }
-    }
-    impl SerializeTupleVariant for Enum {
-        type Ok = &'static str;
-        type Error = NotEnum;
-        fn serialize_field<T: ?Sized + Serialize>(&mut self, _value: &T) -> Result<()> { Ok(()) }
-        fn end(self) -> Result<Self::Ok> {
-            Ok(self.0)
+        fn visit_f64<E: de::Error>(self, v: f64) -> std::result::Result<f64, E> { Ok(v) }
+        fn visit_i64<E: de::Error>(self, v: i64) -> std::result::Result<f64, E> { Ok(v as f64) }
+        fn visit_u64<E: de::Error>(self, v: u64) -> std::result::Result<f64, E> { Ok(v as f64) }
+        fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<f64, E> {
+            v.trim().parse().map_err(|_| E::invalid_value(de::Unexpected::Str(v), &self))
         }
     }
-
-    t.serialize(VariantName).unwrap()
+    deserializer.deserialize_any(Visitor)
 }